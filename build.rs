@@ -41,6 +41,13 @@ fn compile_resource() {
                         accelerators::Modifier::None,
                     ),
                 )
+                .event(
+                    catalog::IDM_FILE_PAUSE,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::Pause,
+                        accelerators::Modifier::None,
+                    ),
+                )
                 .event(
                     catalog::IDM_ADVANCED_RESTART,
                     accelerators::Event::virt_key_event(
@@ -48,6 +55,20 @@ fn compile_resource() {
                         accelerators::Modifier::None,
                     ),
                 )
+                .event(
+                    catalog::IDM_ADVANCED_UNDO,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::Z,
+                        accelerators::Modifier::Control,
+                    ),
+                )
+                .event(
+                    catalog::IDM_ADVANCED_REDO,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::Y,
+                        accelerators::Modifier::Control,
+                    ),
+                )
                 .event(
                     catalog::IDM_ADVANCED_RECORD_STOP,
                     accelerators::Event::virt_key_event(
@@ -55,6 +76,41 @@ fn compile_resource() {
                         accelerators::Modifier::None,
                     ),
                 )
+                .event(
+                    catalog::IDM_ADVANCED_PLAYBACK_STEP_BACKWARD,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::F3,
+                        accelerators::Modifier::None,
+                    ),
+                )
+                .event(
+                    catalog::IDM_ADVANCED_PLAYBACK_STEP_FORWARD,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::F4,
+                        accelerators::Modifier::None,
+                    ),
+                )
+                .event(
+                    catalog::IDM_ADVANCED_PLAYBACK_PAUSE,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::F7,
+                        accelerators::Modifier::None,
+                    ),
+                )
+                .event(
+                    catalog::IDM_ADVANCED_PLAYBACK_SLOWER,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::F9,
+                        accelerators::Modifier::None,
+                    ),
+                )
+                .event(
+                    catalog::IDM_ADVANCED_PLAYBACK_FASTER,
+                    accelerators::Event::virt_key_event(
+                        accelerators::VirtKey::F11,
+                        accelerators::Modifier::None,
+                    ),
+                )
                 .event(
                     catalog::IDM_ADVANCED_LOADMAP,
                     accelerators::Event::virt_key_event(
@@ -95,6 +151,11 @@ fn compile_resource() {
                                 MultiLangText::from("&New\tF2")
                                     .lang(lang::LANG_CHS, "开局(&N)\tF2"),
                             )
+                            .item(
+                                catalog::IDM_FILE_PAUSE,
+                                MultiLangText::from("Pa&use\tPause")
+                                    .lang(lang::LANG_CHS, "暂停(&U)\tPause"),
+                            )
                             .separator()
                             .item(
                                 catalog::IDM_FILE_GAME_EASY,
@@ -114,18 +175,42 @@ fn compile_resource() {
                                 MultiLangText::from("&Custom...")
                                     .lang(lang::LANG_CHS, "自定义(&C)..."),
                             )
+                            .item(
+                                catalog::IDM_FILE_GAME_SEED,
+                                MultiLangText::from("&Seed...").lang(lang::LANG_CHS, "种子(&S)..."),
+                            )
                             .separator()
                             .item(
                                 catalog::IDM_FILE_MARK,
                                 MultiLangText::from("&Marks (?)")
                                     .lang(lang::LANG_CHS, "标记(?)(&M)"),
                             )
+                            .item(
+                                catalog::IDM_FILE_NO_GUESS,
+                                MultiLangText::from("No &Guessing")
+                                    .lang(lang::LANG_CHS, "无需猜测(&G)"),
+                            )
+                            .item(
+                                catalog::IDM_FILE_SAFE_START,
+                                MultiLangText::from("Safe &Opening")
+                                    .lang(lang::LANG_CHS, "首击安全区(&O)"),
+                            )
                             .separator()
                             .item(
                                 catalog::IDM_FILE_HERO_LIST,
                                 MultiLangText::from("Best &Times...")
                                     .lang(lang::LANG_CHS, "扫雷英雄榜(&T)..."),
                             )
+                            .item(
+                                catalog::IDM_FILE_STATISTICS,
+                                MultiLangText::from("St&atistics...")
+                                    .lang(lang::LANG_CHS, "统计信息(&A)..."),
+                            )
+                            .item(
+                                catalog::IDM_FILE_STATISTICS_EXPORT,
+                                MultiLangText::from("Ex&port Statistics...")
+                                    .lang(lang::LANG_CHS, "导出统计信息(&P)..."),
+                            )
                             .separator()
                             .item(
                                 catalog::IDM_FILE_EXIT,
@@ -147,12 +232,47 @@ fn compile_resource() {
                                 MultiLangText::from("&Save Game\tF6")
                                     .lang(lang::LANG_CHS, "保存雷局(&S)\tF6"),
                             )
+                            .item(
+                                catalog::IDM_ADVANCED_BOARD_CODE_EXPORT,
+                                MultiLangText::from("Show Board &Code...")
+                                    .lang(lang::LANG_CHS, "显示雷局代码(&C)..."),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_BOARD_CODE_IMPORT,
+                                MultiLangText::from("E&nter Board Code...")
+                                    .lang(lang::LANG_CHS, "输入雷局代码(&N)..."),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_EXPORT_IMAGE,
+                                MultiLangText::from("Save Board &Image...")
+                                    .lang(lang::LANG_CHS, "保存雷局图片(&I)..."),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_EXPORT_DRAWING,
+                                MultiLangText::from("Save Board Drawin&g...")
+                                    .lang(lang::LANG_CHS, "保存雷局矢量图(&G)..."),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_EXPORT_DRAWING_LABELED,
+                                MultiLangText::from("Save Drawing with C&oordinates...")
+                                    .lang(lang::LANG_CHS, "保存带坐标的矢量图(&O)..."),
+                            )
                             .separator()
                             .item(
                                 catalog::IDM_ADVANCED_RESTART,
                                 MultiLangText::from("&Restart Game\tF8")
                                     .lang(lang::LANG_CHS, "重新开始本局(&R)\tF8"),
                             )
+                            .item(
+                                catalog::IDM_ADVANCED_UNDO,
+                                MultiLangText::from("&Undo\tCtrl+Z")
+                                    .lang(lang::LANG_CHS, "撤销(&U)\tCtrl+Z"),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_REDO,
+                                MultiLangText::from("Re&do\tCtrl+Y")
+                                    .lang(lang::LANG_CHS, "重做(&D)\tCtrl+Y"),
+                            )
                             .separator()
                             .item(
                                 catalog::IDM_ADVANCED_RECORD_RECORD,
@@ -170,6 +290,47 @@ fn compile_resource() {
                                     .lang(lang::LANG_CHS, "停止(&T)\tF12"),
                             )
                             .separator()
+                            .item(
+                                catalog::IDM_ADVANCED_PLAYBACK_PAUSE,
+                                MultiLangText::from("Pa&use/Resume Playback\tF7")
+                                    .lang(lang::LANG_CHS, "暂停/继续回放(&U)\tF7"),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_PLAYBACK_STEP_BACKWARD,
+                                MultiLangText::from("Step &Backward\tF3")
+                                    .lang(lang::LANG_CHS, "后退一步(&B)\tF3"),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_PLAYBACK_STEP_FORWARD,
+                                MultiLangText::from("Step &Forward\tF4")
+                                    .lang(lang::LANG_CHS, "前进一步(&F)\tF4"),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_PLAYBACK_JUMP,
+                                MultiLangText::from("&Jump to Move...")
+                                    .lang(lang::LANG_CHS, "跳转到指定步(&J)..."),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_PLAYBACK_SLOWER,
+                                MultiLangText::from("Play Slo&wer\tF9")
+                                    .lang(lang::LANG_CHS, "减速回放(&W)\tF9"),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_PLAYBACK_FASTER,
+                                MultiLangText::from("Play F&aster\tF11")
+                                    .lang(lang::LANG_CHS, "加速回放(&A)\tF11"),
+                            )
+                            .separator()
+                            .item(
+                                catalog::IDM_ADVANCED_HINT,
+                                MultiLangText::from("&Hint").lang(lang::LANG_CHS, "提示(&H)"),
+                            )
+                            .item(
+                                catalog::IDM_ADVANCED_HEATMAP,
+                                MultiLangText::from("Mine &Probabilities")
+                                    .lang(lang::LANG_CHS, "地雷概率(&P)"),
+                            )
+                            .separator()
                             .item(
                                 catalog::IDM_ADVANCED_ZOOM_1x,
                                 MultiLangText::from("Zoom 1x").lang(lang::LANG_CHS, "缩放 1x"),
@@ -252,6 +413,432 @@ fn compile_resource() {
                 )
                 .build(),
         )
+        .resource(
+            catalog::IDD_CUSTOM_GAME,
+            resource::Dialog::from_builder()
+                .system_menu()
+                .caption(MultiLangText::from("Custom Game").lang(lang::LANG_CHS, "自定义雷区"))
+                .font(
+                    "Tahoma",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .lang_specific_font(
+                    lang::LANG_CHS,
+                    "SimSun",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .style(dialog::DialogStyle::MODAL_FRAME)
+                .rect(Rect::new(0, 0, 129, 85))
+                .control(
+                    catalog::IDC_TEXT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("&Height:").lang(lang::LANG_CHS, "高度(&H):"))
+                        .rect(Rect::new(10, 22, 35, 10)),
+                )
+                .control(
+                    catalog::IDC_EDIT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::EDITTEXT)
+                        .rect(Rect::new(46, 20, 25, 12)),
+                )
+                .control(
+                    catalog::IDC_TEXT2,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("&Width:").lang(lang::LANG_CHS, "宽度(&W):"))
+                        .rect(Rect::new(10, 37, 35, 10)),
+                )
+                .control(
+                    catalog::IDC_EDIT2,
+                    dialog::Control::from_template(dialog::ControlTemplate::EDITTEXT)
+                        .rect(Rect::new(46, 35, 25, 12)),
+                )
+                .control(
+                    catalog::IDC_TEXT3,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("&Mines:").lang(lang::LANG_CHS, "雷数(&M):"))
+                        .rect(Rect::new(10, 52, 35, 10)),
+                )
+                .control(
+                    catalog::IDC_EDIT3,
+                    dialog::Control::from_template(dialog::ControlTemplate::EDITTEXT)
+                        .rect(Rect::new(46, 50, 25, 12)),
+                )
+                .control(
+                    predefined_id::OK,
+                    dialog::Control::from_template(dialog::ControlTemplate::DEFPUSHBUTTON)
+                        .text(MultiLangText::from("OK").lang(lang::LANG_CHS, "确定"))
+                        .rect(Rect::new(80, 20, 40, 16))
+                        .style(dialog::WindowStyle::GROUP),
+                )
+                .control(
+                    predefined_id::CANCEL,
+                    dialog::Control::from_template(dialog::ControlTemplate::PUSHBUTTON)
+                        .text(MultiLangText::from("Cancel").lang(lang::LANG_CHS, "取消"))
+                        .rect(Rect::new(80, 46, 40, 16)),
+                )
+                .build(),
+        )
+        .resource(
+            catalog::IDD_HERO_NAME,
+            resource::Dialog::from_builder()
+                .font(
+                    "Tahoma",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .lang_specific_font(
+                    lang::LANG_CHS,
+                    "SimSun",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .style(dialog::DialogStyle::MODAL_FRAME)
+                .rect(Rect::new(0, 28, 100, 109))
+                .control(
+                    catalog::IDC_TEXT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::CTEXT)
+                        .rect(Rect::new(6, 5, 88, 40)),
+                )
+                .control(
+                    catalog::IDC_EDIT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::EDITTEXT)
+                        .rect(Rect::new(12, 51, 78, 12)),
+                )
+                .control(
+                    predefined_id::OK,
+                    dialog::Control::from_template(dialog::ControlTemplate::DEFPUSHBUTTON)
+                        .text(MultiLangText::from("OK").lang(lang::LANG_CHS, "确定"))
+                        .rect(Rect::new(32, 72, 36, 14))
+                        .style(dialog::WindowStyle::GROUP),
+                )
+                .build(),
+        )
+        .resource(
+            catalog::IDD_HERO_LIST,
+            resource::Dialog::from_builder()
+                .system_menu()
+                .caption(
+                    MultiLangText::from("Fastest Mine Sweepers").lang(lang::LANG_CHS, "扫雷英雄榜"),
+                )
+                .font(
+                    "Tahoma",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .lang_specific_font(
+                    lang::LANG_CHS,
+                    "SimSun",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .style(dialog::DialogStyle::MODAL_FRAME)
+                .rect(Rect::new(0, 28, 170, 75))
+                .control(
+                    catalog::IDC_TEXT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("Easy:").lang(lang::LANG_CHS, "初级:"))
+                        .rect(Rect::new(10, 15, 29, 8)),
+                )
+                .control(
+                    catalog::IDC_EDIT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(38, 15, 44, 8)),
+                )
+                .control(
+                    catalog::IDC_EXTRA1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(92, 15, 76, 8)),
+                )
+                .control(
+                    catalog::IDC_TEXT2,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("Medium:").lang(lang::LANG_CHS, "中级:"))
+                        .rect(Rect::new(10, 25, 29, 8)),
+                )
+                .control(
+                    catalog::IDC_EDIT2,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(38, 25, 44, 8)),
+                )
+                .control(
+                    catalog::IDC_EXTRA2,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(92, 25, 76, 8)),
+                )
+                .control(
+                    catalog::IDC_TEXT3,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("Expert:").lang(lang::LANG_CHS, "高级:"))
+                        .rect(Rect::new(10, 35, 29, 8)),
+                )
+                .control(
+                    catalog::IDC_EDIT3,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(38, 35, 44, 8)),
+                )
+                .control(
+                    catalog::IDC_EXTRA3,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(92, 35, 76, 8)),
+                )
+                .control(
+                    catalog::IDC_BUTTON1,
+                    dialog::Control::from_template(dialog::ControlTemplate::PUSHBUTTON)
+                        .text(
+                            MultiLangText::from("&Reset Scores")
+                                .lang(lang::LANG_CHS, "重新计分(&R)"),
+                        )
+                        .rect(Rect::new(25, 55, 50, 12)),
+                )
+                .control(
+                    predefined_id::OK,
+                    dialog::Control::from_template(dialog::ControlTemplate::DEFPUSHBUTTON)
+                        .text(MultiLangText::from("OK").lang(lang::LANG_CHS, "确定"))
+                        .rect(Rect::new(115, 55, 30, 12))
+                        .style(dialog::WindowStyle::GROUP),
+                )
+                .build(),
+        )
+        .resource(
+            catalog::IDD_PLAYBACK_JUMP,
+            resource::Dialog::from_builder()
+                .system_menu()
+                .caption(MultiLangText::from("Jump to Move").lang(lang::LANG_CHS, "跳转"))
+                .font(
+                    "Tahoma",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .lang_specific_font(
+                    lang::LANG_CHS,
+                    "SimSun",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .style(dialog::DialogStyle::MODAL_FRAME)
+                .rect(Rect::new(0, 0, 150, 60))
+                .control(
+                    catalog::IDC_TEXT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("&Move:").lang(lang::LANG_CHS, "步数(&M):"))
+                        .rect(Rect::new(10, 12, 80, 10)),
+                )
+                .control(
+                    catalog::IDC_EDIT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::EDITTEXT)
+                        .rect(Rect::new(10, 26, 80, 12)),
+                )
+                .control(
+                    predefined_id::OK,
+                    dialog::Control::from_template(dialog::ControlTemplate::DEFPUSHBUTTON)
+                        .text(MultiLangText::from("OK").lang(lang::LANG_CHS, "确定"))
+                        .rect(Rect::new(100, 8, 40, 16))
+                        .style(dialog::WindowStyle::GROUP),
+                )
+                .control(
+                    predefined_id::CANCEL,
+                    dialog::Control::from_template(dialog::ControlTemplate::PUSHBUTTON)
+                        .text(MultiLangText::from("Cancel").lang(lang::LANG_CHS, "取消"))
+                        .rect(Rect::new(100, 30, 40, 16)),
+                )
+                .build(),
+        )
+        .resource(
+            catalog::IDD_GAME_SEED,
+            resource::Dialog::from_builder()
+                .system_menu()
+                .caption(MultiLangText::from("Game Seed").lang(lang::LANG_CHS, "游戏种子"))
+                .font(
+                    "Tahoma",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .lang_specific_font(
+                    lang::LANG_CHS,
+                    "SimSun",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .style(dialog::DialogStyle::MODAL_FRAME)
+                .rect(Rect::new(0, 0, 150, 60))
+                .control(
+                    catalog::IDC_TEXT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("&Seed:").lang(lang::LANG_CHS, "种子(&S):"))
+                        .rect(Rect::new(10, 12, 80, 10)),
+                )
+                .control(
+                    catalog::IDC_EDIT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::EDITTEXT)
+                        .rect(Rect::new(10, 26, 80, 12)),
+                )
+                .control(
+                    predefined_id::OK,
+                    dialog::Control::from_template(dialog::ControlTemplate::DEFPUSHBUTTON)
+                        .text(MultiLangText::from("OK").lang(lang::LANG_CHS, "确定"))
+                        .rect(Rect::new(100, 8, 40, 16))
+                        .style(dialog::WindowStyle::GROUP),
+                )
+                .control(
+                    predefined_id::CANCEL,
+                    dialog::Control::from_template(dialog::ControlTemplate::PUSHBUTTON)
+                        .text(MultiLangText::from("Cancel").lang(lang::LANG_CHS, "取消"))
+                        .rect(Rect::new(100, 30, 40, 16)),
+                )
+                .build(),
+        )
+        .resource(
+            catalog::IDD_BOARD_CODE,
+            resource::Dialog::from_builder()
+                .system_menu()
+                .caption(MultiLangText::from("Board Code").lang(lang::LANG_CHS, "雷局代码"))
+                .font(
+                    "Tahoma",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .lang_specific_font(
+                    lang::LANG_CHS,
+                    "SimSun",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .style(dialog::DialogStyle::MODAL_FRAME)
+                .rect(Rect::new(0, 0, 260, 70))
+                .control(
+                    catalog::IDC_TEXT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(10, 8, 240, 10)),
+                )
+                .control(
+                    catalog::IDC_EDIT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::EDITTEXT)
+                        .rect(Rect::new(10, 22, 240, 12)),
+                )
+                .control(
+                    predefined_id::OK,
+                    dialog::Control::from_template(dialog::ControlTemplate::DEFPUSHBUTTON)
+                        .text(MultiLangText::from("OK").lang(lang::LANG_CHS, "确定"))
+                        .rect(Rect::new(160, 44, 40, 16))
+                        .style(dialog::WindowStyle::GROUP),
+                )
+                .control(
+                    predefined_id::CANCEL,
+                    dialog::Control::from_template(dialog::ControlTemplate::PUSHBUTTON)
+                        .text(MultiLangText::from("Cancel").lang(lang::LANG_CHS, "取消"))
+                        .rect(Rect::new(210, 44, 40, 16)),
+                )
+                .build(),
+        )
+        .resource(
+            catalog::IDD_STATISTICS,
+            resource::Dialog::from_builder()
+                .system_menu()
+                .caption(MultiLangText::from("Statistics").lang(lang::LANG_CHS, "统计信息"))
+                .font(
+                    "Tahoma",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .lang_specific_font(
+                    lang::LANG_CHS,
+                    "SimSun",
+                    FontSize::pt(9),
+                    FontWeight::default(),
+                    FontItalic::default(),
+                    FontCharset::default(),
+                )
+                .style(dialog::DialogStyle::MODAL_FRAME)
+                .rect(Rect::new(0, 28, 300, 90))
+                .control(
+                    catalog::IDC_TEXT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("Easy:").lang(lang::LANG_CHS, "初级:"))
+                        .rect(Rect::new(10, 15, 34, 8)),
+                )
+                .control(
+                    catalog::IDC_EDIT1,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(44, 15, 246, 8)),
+                )
+                .control(
+                    catalog::IDC_TEXT2,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("Medium:").lang(lang::LANG_CHS, "中级:"))
+                        .rect(Rect::new(10, 27, 34, 8)),
+                )
+                .control(
+                    catalog::IDC_EDIT2,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(44, 27, 246, 8)),
+                )
+                .control(
+                    catalog::IDC_TEXT3,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("Expert:").lang(lang::LANG_CHS, "高级:"))
+                        .rect(Rect::new(10, 39, 34, 8)),
+                )
+                .control(
+                    catalog::IDC_EDIT3,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(44, 39, 246, 8)),
+                )
+                .control(
+                    catalog::IDC_TEXT4,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .text(MultiLangText::from("Custom:").lang(lang::LANG_CHS, "自定义:"))
+                        .rect(Rect::new(10, 51, 34, 8)),
+                )
+                .control(
+                    catalog::IDC_EDIT4,
+                    dialog::Control::from_template(dialog::ControlTemplate::LTEXT)
+                        .rect(Rect::new(44, 51, 246, 8)),
+                )
+                .control(
+                    catalog::IDC_BUTTON1,
+                    dialog::Control::from_template(dialog::ControlTemplate::PUSHBUTTON)
+                        .text(
+                            MultiLangText::from("&Reset Statistics")
+                                .lang(lang::LANG_CHS, "清除统计(&R)"),
+                        )
+                        .rect(Rect::new(60, 70, 70, 12)),
+                )
+                .control(
+                    predefined_id::OK,
+                    dialog::Control::from_template(dialog::ControlTemplate::DEFPUSHBUTTON)
+                        .text(MultiLangText::from("OK").lang(lang::LANG_CHS, "确定"))
+                        .rect(Rect::new(170, 70, 30, 12))
+                        .style(dialog::WindowStyle::GROUP),
+                )
+                .build(),
+        )
         .compile()
         .expect("Failed to compile resource");
 }
@@ -1,5 +1,6 @@
 use crate::model::{self, Model, ModelCommand};
 use crate::model_config::{self, Config};
+use crate::mvc::GameModel;
 use crate::view::{self, GameTarget, View};
 use apiw::graphics_subsystem::Point;
 use apiw::graphics_subsystem::Rect;
@@ -161,9 +162,9 @@ impl Controller {
     }
 }
 
-type ControllerToken<'a> = ::domino::mvc::ControllerToken<'a, model::Model, view::View, Controller>;
+type ControllerToken<'a> = ::domino::mvc::ControllerToken<'a, GameModel, view::View, Controller>;
 
-impl ::domino::mvc::Controller<model::Model, view::View> for Controller {
+impl ::domino::mvc::Controller<GameModel, view::View> for Controller {
     type Command = ControllerInput;
     type Notification = ModelCommand;
 
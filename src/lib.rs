@@ -0,0 +1,20 @@
+//! Board, configuration and game-mode logic of CharlesMine, along with the
+//! layout and drawing of the game area, free of any platform code so that it
+//! builds and is tested everywhere. UI backends such as the Windows one in
+//! `ui_apiw.rs` implement the traits in `ui_backend`; `ui_software` paints
+//! into an in-memory framebuffer instead.
+
+pub mod model;
+pub mod model_config;
+pub mod model_gamemode;
+pub mod model_hero;
+pub mod model_random;
+pub mod model_solver;
+pub mod model_stats;
+pub mod model_storage;
+pub mod ui_backend;
+pub mod ui_software;
+pub mod view_assets;
+pub mod view_command;
+pub mod view_export;
+pub mod view_layout;
@@ -1,46 +1,107 @@
 #![windows_subsystem = "windows"]
 #![allow(unused_imports, unreachable_code, unused_variables, dead_code)]
 
+#[cfg(windows)]
 use crate::ui::Ui;
+use charlesmine::{
+    model, model_config, model_gamemode, model_hero, model_solver, model_stats, model_storage,
+    ui_backend, view_assets, view_command, view_export, view_layout,
+};
 use std::cell::RefCell;
 
+#[cfg(windows)]
 mod controller;
-mod model;
-mod model_config;
-mod model_gamemode;
+#[cfg(windows)]
+mod mvc;
+#[cfg(windows)]
 #[path = "ui_apiw.rs"]
 mod ui;
+#[cfg(windows)]
 mod view;
-mod view_assets;
-
-type GameMVC = domino::mvc::MVCSystem<model::Model, view::View, controller::Controller>;
 
+#[cfg(windows)]
 struct Game {
-    pub mvc: GameMVC,
+    pub mvc: mvc::GameMVC,
 }
 
+#[cfg(windows)]
 impl Game {
     fn new() -> Self {
-        let model = model::Model::new();
+        let model = mvc::GameModel::new();
         let view = view::View::new(&model);
         let controller = controller::Controller::new(&model);
-        let mvc = GameMVC::new(model, view, controller);
+        let mvc = mvc::GameMVC::new(model, view, controller);
         Game { mvc }
     }
 }
 
+#[cfg(windows)]
 thread_local! {
     static THE_GAME: RefCell<Game> = RefCell::new(Game::new());
 }
 
+/// A windows subsystem program has no console to print to, so errors are
+/// shown in a message box there as well.
+fn report_error(msg: &str) {
+    eprintln!("{}", msg);
+    #[cfg(windows)]
+    ui::ui_alert(msg);
+}
+
+/// `--export-stats <file>` writes the game history to a `.csv` or `.json`
+/// file and exits without opening the window. The exit code tells whether
+/// the export worked.
+fn run_command_line() -> Option<i32> {
+    let mut args = std::env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg != "--export-stats" {
+            continue;
+        }
+        let path = match args.next() {
+            Some(path) => std::path::PathBuf::from(path),
+            None => {
+                report_error("--export-stats needs an output file");
+                return Some(2);
+            }
+        };
+        let game_history = model_stats::GameHistory::load(&model_storage::Storage::user_dirs());
+        return match game_history.export_to_file(&path) {
+            Ok(()) => Some(0),
+            Err(failure) => {
+                report_error(&format!("{}: {}", path.display(), failure));
+                Some(1)
+            }
+        };
+    }
+    None
+}
+
+#[cfg(windows)]
 fn main() -> apiw::Result<()> {
     use crate::view::View;
 
     env_logger::init();
 
+    if let Some(code) = run_command_line() {
+        std::process::exit(code);
+    }
+
     Ui::initialization()?;
 
     Ui::run_event_loop()?;
 
     return Ok(());
 }
+
+#[cfg(not(windows))]
+fn main() {
+    env_logger::init();
+
+    if let Some(code) = run_command_line() {
+        std::process::exit(code);
+    }
+
+    eprintln!("The game window is only available on Windows.");
+    eprintln!("Usage: charlesmine --export-stats <file.csv|file.json>");
+    std::process::exit(2);
+}
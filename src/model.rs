@@ -1,18 +1,21 @@
 #![allow(dead_code)]
 
 use chrono::{DateTime, Local};
-use rand::{self, distributions::Uniform, Rng};
 
-use crate::controller;
 use crate::model_config::{self, Config};
-use crate::model_gamemode::{self, BoardSaved, GameMode};
-use crate::view::AlertFailure;
-use crate::view::{self, ViewCommand};
-use std::cell::Cell;
-use std::collections::BTreeSet;
+use crate::model_gamemode::{BoardSaved, GameMode, GameRecord, GameRecorder, Playback};
+use crate::model_hero::{HeroList, HeroRecord};
+use crate::model_random::{self, SeedRng};
+use crate::model_solver::{self, SolverCell, SolverGrid};
+use crate::model_stats::{ClickStats, GameHistory, GameResult, GameStats, PlayMode};
+use crate::model_storage::Storage;
+use crate::view_command::{AlertFailure, AlertNotice, ViewCommand};
+use crate::view_export;
+use std::cell::{Cell, RefCell};
 use std::ops;
 use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 #[derive(Copy, Clone, PartialEq, Eq, Debug)]
 pub enum BlockStatus {
@@ -68,6 +71,66 @@ pub enum BoardStatus {
     Died(DateTime<Local>, DateTime<Local>),
 }
 
+/// What a generated layout depends on besides the board size and mine count,
+/// enough to generate the same layout again.
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct BoardSeed {
+    pub seed: u64,
+    pub first_click: (usize, usize),
+    pub safe_start: bool,
+    pub no_guess: bool,
+}
+
+const NO_GUESS_ATTEMPT_LIMIT: usize = 200;
+
+/// Play time on the monotonic clock, which unlike the wall clock never jumps,
+/// and which stands still while the game is paused.
+#[derive(Clone, Debug)]
+pub struct GameClock {
+    elapsed: Duration,
+    running_since: Option<Instant>,
+    now: fn() -> Instant,
+}
+
+impl Default for GameClock {
+    fn default() -> Self {
+        GameClock::with_time_source(Instant::now)
+    }
+}
+
+impl GameClock {
+    /// A stopped clock that reads the time from `now`.
+    fn with_time_source(now: fn() -> Instant) -> Self {
+        GameClock {
+            elapsed: Duration::from_millis(0),
+            running_since: None,
+            now,
+        }
+    }
+
+    fn start(&mut self) {
+        if self.running_since.is_none() {
+            self.running_since = Some((self.now)());
+        }
+    }
+
+    fn stop(&mut self) {
+        if let Some(running_since) = self.running_since.take() {
+            self.elapsed += (self.now)().saturating_duration_since(running_since);
+        }
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        match self.running_since {
+            Some(running_since) => {
+                self.elapsed + (self.now)().saturating_duration_since(running_since)
+            }
+            None => self.elapsed,
+        }
+    }
+}
+
+#[derive(Clone)]
 pub struct Board {
     size: (usize, usize),
     count: usize,
@@ -76,7 +139,20 @@ pub struct Board {
     status: BoardStatus,
     blocks: Vec<Block>,
     fixed_mine_pos: Option<Rc<Vec<usize>>>,
+    seed: u64,
+    board_seed: Option<BoardSeed>,
+    no_guess_fallback: bool,
     allow_marks: bool,
+    no_guess: bool,
+    safe_start: bool,
+    hint_pos: Option<(usize, usize)>,
+    hint_count: usize,
+    click_stats: ClickStats,
+    clock: GameClock,
+    paused: bool,
+    practice: bool,
+    heatmap_used: bool,
+    kind: model_config::BoardSettingKind,
 }
 
 impl Board {
@@ -89,7 +165,20 @@ impl Board {
             status: BoardStatus::Ready,
             blocks: vec![Default::default(); y * x],
             fixed_mine_pos: None,
+            seed: model_random::random_seed(),
+            board_seed: None,
+            no_guess_fallback: false,
             allow_marks: true,
+            no_guess: false,
+            safe_start: false,
+            hint_pos: None,
+            hint_count: 0,
+            click_stats: ClickStats::default(),
+            clock: GameClock::default(),
+            paused: false,
+            practice: false,
+            heatmap_used: false,
+            kind: model_config::BoardSettingKind::Custom,
         }
     }
 
@@ -99,6 +188,12 @@ impl Board {
         let fixed_mine_pos = self.fixed_mine_pos.clone();
         let mut board = Board::new(size.0, size.1, count);
         board.fixed_mine_pos = fixed_mine_pos;
+        board.seed = self.seed;
+        board.allow_marks = self.allow_marks;
+        board.no_guess = self.no_guess;
+        board.safe_start = self.safe_start;
+        board.kind = self.kind;
+        board.clock = GameClock::with_time_source(self.clock.now);
         board
     }
 
@@ -122,6 +217,209 @@ impl Board {
         self.status.clone()
     }
 
+    pub fn hint_pos(&self) -> Option<(usize, usize)> {
+        self.hint_pos
+    }
+
+    pub fn hint_count(&self) -> usize {
+        self.hint_count
+    }
+
+    pub fn is_practice(&self) -> bool {
+        self.practice
+    }
+
+    /// Whether the mine probabilities were on screen at any point of the game.
+    pub fn is_heatmap_used(&self) -> bool {
+        self.heatmap_used
+    }
+
+    pub(crate) fn set_heatmap_used(&mut self) {
+        self.heatmap_used = true;
+    }
+
+    /// The difficulty this board was set up with; loaded maps are custom.
+    pub fn kind(&self) -> model_config::BoardSettingKind {
+        self.kind
+    }
+
+    /// Board 3BV: the fewest clicks that clear the board, one per opening
+    /// (connected area of zeros with its border) plus one per numbered cell
+    /// that borders no opening. Unknown until the mines are placed.
+    pub fn bbbv(&self) -> Option<usize> {
+        if self.status == BoardStatus::Ready {
+            return None;
+        }
+        let (h, w) = self.size;
+        let mut visited = vec![false; h * w];
+        let mut count = 0;
+        for y in 0..h {
+            for x in 0..w {
+                let idx = self.block_data_idx(y, x);
+                if visited[idx] || self.block_display_number(y, x) != Some(0) {
+                    continue;
+                }
+                count += 1;
+                visited[idx] = true;
+                let mut stack = vec![(y, x)];
+                while let Some((y, x)) = stack.pop() {
+                    for &(y, x) in &Self::surrounding_blocks(y as isize, x as isize) {
+                        if !Self::is_index_in_range(self, y, x) {
+                            continue;
+                        }
+                        let (y, x) = (y as usize, x as usize);
+                        let idx = self.block_data_idx(y, x);
+                        if visited[idx] {
+                            continue;
+                        }
+                        match self.block_display_number(y, x) {
+                            Some(0) => {
+                                visited[idx] = true;
+                                stack.push((y, x));
+                            }
+                            Some(_) => visited[idx] = true,
+                            None => {}
+                        }
+                    }
+                }
+            }
+        }
+        for y in 0..h {
+            for x in 0..w {
+                let idx = self.block_data_idx(y, x);
+                if !visited[idx] && self.block_display_number(y, x).is_some() {
+                    count += 1;
+                }
+            }
+        }
+        Some(count)
+    }
+
+    pub fn click_stats(&self) -> &ClickStats {
+        &self.click_stats
+    }
+
+    pub fn game_stats(&self) -> Option<GameStats> {
+        let won = match self.status {
+            BoardStatus::Finished(..) => true,
+            BoardStatus::Died(..) => false,
+            _ => return None,
+        };
+        Some(GameStats {
+            won,
+            time: self.clock.elapsed(),
+            bbbv: self.bbbv()?,
+            clicks: self.click_stats.clone(),
+        })
+    }
+
+    pub fn elapsed_time(&self) -> Duration {
+        self.clock.elapsed()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Times the game with `now` instead of the monotonic clock. Only meant
+    /// for a board that has not started yet.
+    pub(crate) fn set_time_source(&mut self, now: fn() -> Instant) {
+        self.clock = GameClock::with_time_source(now);
+    }
+
+    /// Only a game in progress can be paused.
+    pub(crate) fn set_paused(&mut self, paused: bool) {
+        match self.status {
+            BoardStatus::Going(..) => {}
+            _ => return,
+        }
+        if self.paused == paused {
+            return;
+        }
+        self.paused = paused;
+        if paused {
+            self.clock.stop();
+        } else {
+            self.clock.start();
+        }
+    }
+
+    fn is_same_progress(&self, other: &Board) -> bool {
+        self.status == other.status
+            && self
+                .blocks
+                .iter()
+                .zip(other.blocks.iter())
+                .all(|(a, b)| a.status == b.status)
+    }
+
+    /// Points out a block that is certainly safe, or the least risky one if
+    /// every remaining block needs a guess.
+    pub(crate) fn show_hint(&mut self) {
+        if self.hint_pos.is_some() {
+            return;
+        }
+        let hint_pos = match self.status {
+            BoardStatus::Ready => match self.fixed_mine_pos.as_ref() {
+                // the first click never lands on a generated mine.
+                None => (self.size.0 / 2, self.size.1 / 2),
+                Some(mine_pos) => match self.first_safe_block(mine_pos) {
+                    Some(pos) => pos,
+                    None => return,
+                },
+            },
+            BoardStatus::Going(..) => {
+                let grid = SolverGrid::from_board_without_flags(self);
+                let mut candidates = model_solver::deduce(&grid).safe;
+                if candidates.is_empty() {
+                    let unknown = (0..grid.cells().len())
+                        .filter(|&idx| grid.cell(idx) == SolverCell::Unknown)
+                        .collect::<Vec<_>>();
+                    candidates = match model_solver::mine_probabilities(&grid) {
+                        Some(probabilities) => unknown
+                            .into_iter()
+                            .min_by(|&a, &b| {
+                                probabilities[a]
+                                    .partial_cmp(&probabilities[b])
+                                    .unwrap_or(std::cmp::Ordering::Equal)
+                            })
+                            .into_iter()
+                            .collect(),
+                        None => unknown,
+                    };
+                }
+                match candidates.first() {
+                    Some(&idx) => grid.position(idx),
+                    None => return,
+                }
+            }
+            BoardStatus::Finished(..) | BoardStatus::Died(..) => return,
+        };
+        self.hint_pos = Some(hint_pos);
+        self.hint_count += 1;
+    }
+
+    /// A block of the fixed layout `mine_pos` to start with: one that opens a
+    /// region if there is any, nearest to the center.
+    fn first_safe_block(&self, mine_pos: &[usize]) -> Option<(usize, usize)> {
+        let (height, width) = self.size;
+        let mut has_mine = vec![false; height * width];
+        for &mine_idx in mine_pos {
+            has_mine[mine_idx] = true;
+        }
+        let center = ((height / 2) as isize, (width / 2) as isize);
+        (0..height * width)
+            .filter(|&idx| !has_mine[idx])
+            .min_by_key(|&idx| {
+                let (y, x) = ((idx / width) as isize, (idx % width) as isize);
+                let next_to_mine = Self::surrounding_blocks(y, x).iter().any(|&(y, x)| {
+                    Self::is_index_in_range(self, y, x) && has_mine[y as usize * width + x as usize]
+                });
+                (next_to_mine, (y - center.0).abs() + (x - center.1).abs())
+            })
+            .map(|idx| (idx / width, idx % width))
+    }
+
     pub fn fixed_mine_pos_list(&self) -> Option<&Rc<Vec<usize>>> {
         self.fixed_mine_pos.as_ref()
     }
@@ -130,6 +428,20 @@ impl Board {
         self.fixed_mine_pos = list;
     }
 
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    pub(crate) fn set_seed(&mut self, seed: u64) {
+        self.seed = seed;
+    }
+
+    /// How the current layout was generated, if it was generated on the first
+    /// click rather than given as a fixed mine list.
+    pub fn board_seed(&self) -> Option<BoardSeed> {
+        self.board_seed
+    }
+
     pub fn snapshot_mine_pos_list(&self) -> Option<Vec<usize>> {
         if self.status == BoardStatus::Ready {
             None
@@ -146,21 +458,72 @@ impl Board {
 
     pub fn allocate_mine_pos_list(&self, exclude_pos: Option<(usize, usize)>) -> Vec<usize> {
         let mapsize = self.size.0 * self.size.1;
-        let mut exclude_set = BTreeSet::new();
-        if let Some((y, x)) = exclude_pos {
-            exclude_set.insert(self.block_data_idx(y, x));
+        let exclude_list = match exclude_pos {
+            Some((y, x)) => self.first_click_exclude_list(y, x, self.safe_start),
+            None => Vec::new(),
         };
-        let mut rng = rand::thread_rng();
-        let mut result = Vec::new();
-        for mine_idx in Rng::sample_iter(&mut rng, &Uniform::new_inclusive(0, mapsize - 1)) {
-            if exclude_set.replace(mine_idx).is_none() {
-                result.push(mine_idx);
+        model_random::pick_mine_pos_list(self.seed, mapsize, self.count, &exclude_list)
+    }
+
+    /// Cells that must stay free of mines when the first click lands on
+    /// `(y, x)`. With `with_neighbors`, the whole neighborhood is kept clear
+    /// so the click opens a region, unless the board is too dense to leave
+    /// room for every mine, in which case only the clicked cell is spared.
+    fn first_click_exclude_list(&self, y: usize, x: usize, with_neighbors: bool) -> Vec<usize> {
+        let mapsize = self.size.0 * self.size.1;
+        let mut exclude_list = vec![self.block_data_idx(y, x)];
+        if with_neighbors {
+            for &(y, x) in Self::surrounding_blocks(y as isize, x as isize).iter() {
+                if Self::is_index_in_range(self, y, x) {
+                    exclude_list.push(self.block_data_idx(y as usize, x as usize));
+                }
             }
-            if result.len() == self.count {
-                break;
+            if mapsize - exclude_list.len() < self.count {
+                exclude_list.truncate(1);
             }
         }
-        result
+        exclude_list
+    }
+
+    /// The layout `board_seed` generates on a board of this size and mine
+    /// count, the same way the first click does, and whether no-guess mode had
+    /// to fall back to a layout that may need a guess.
+    pub(crate) fn generate_mine_pos_list(&self, board_seed: BoardSeed) -> (Vec<usize>, bool) {
+        let (y, x) = board_seed.first_click;
+        if board_seed.no_guess {
+            return self.allocate_no_guess_mine_pos_list(board_seed.seed, y, x);
+        }
+        let mapsize = self.size.0 * self.size.1;
+        let exclude_list = self.first_click_exclude_list(y, x, board_seed.safe_start);
+        let mine_pos_list =
+            model_random::pick_mine_pos_list(board_seed.seed, mapsize, self.count, &exclude_list);
+        (mine_pos_list, false)
+    }
+
+    /// Tries seeds derived from `seed` until the solver can clear the layout
+    /// from the first click. Gives up after `NO_GUESS_ATTEMPT_LIMIT` layouts,
+    /// falling back to the first one, which dense boards may never beat; the
+    /// returned flag tells when that happened.
+    /// The result only depends on the seed and the first click.
+    fn allocate_no_guess_mine_pos_list(&self, seed: u64, y: usize, x: usize) -> (Vec<usize>, bool) {
+        let mapsize = self.size.0 * self.size.1;
+        let start = self.block_data_idx(y, x);
+        let exclude_list = self.first_click_exclude_list(y, x, true);
+
+        let mut seed_rng = SeedRng::new(seed);
+        let first_list = model_random::pick_mine_pos_list(seed, mapsize, self.count, &exclude_list);
+        if model_solver::is_solvable_without_guess(self.size, &first_list, start) {
+            return (first_list, false);
+        }
+        for _ in 1..NO_GUESS_ATTEMPT_LIMIT {
+            let seed = seed_rng.next_u64();
+            let mine_pos_list =
+                model_random::pick_mine_pos_list(seed, mapsize, self.count, &exclude_list);
+            if model_solver::is_solvable_without_guess(self.size, &mine_pos_list, start) {
+                return (mine_pos_list, false);
+            }
+        }
+        (first_list, true)
     }
 
     #[inline]
@@ -194,7 +557,15 @@ impl Board {
                 self.blocks[mine_idx].has_mine = true;
             }
         } else {
-            let mine_pos_list = self.allocate_mine_pos_list(Some((y, x)));
+            let board_seed = BoardSeed {
+                seed: self.seed,
+                first_click: (y, x),
+                safe_start: self.safe_start,
+                no_guess: self.no_guess,
+            };
+            let (mine_pos_list, no_guess_fallback) = self.generate_mine_pos_list(board_seed);
+            self.board_seed = Some(board_seed);
+            self.no_guess_fallback = no_guess_fallback;
             for mine_idx in mine_pos_list {
                 assert!(self.blocks[mine_idx].status == BlockStatus::Normal);
                 if self.blocks[mine_idx].has_mine {
@@ -243,7 +614,7 @@ impl Board {
         false
     }
 
-    fn surrounding_blocks(y: isize, x: isize) -> [(isize, isize); 12] {
+    pub(crate) fn surrounding_blocks(y: isize, x: isize) -> [(isize, isize); 12] {
         [
             (y - 1, x - 1),
             (y - 1, x + 0),
@@ -379,8 +750,10 @@ impl Board {
         if let BoardStatus::Going(start_time) = self.status {
             if self.rest_count == self.count {
                 self.prepare_for_finish();
+                self.clock.stop();
                 self.status = BoardStatus::Finished(start_time, Local::now());
             } else if exploded {
+                self.clock.stop();
                 self.status = BoardStatus::Died(start_time, Local::now());
             }
         }
@@ -392,6 +765,7 @@ impl Board {
 
         if self.status == BoardStatus::Ready {
             self.start_game_with(y, x);
+            self.clock.start();
             self.status = BoardStatus::Going(Local::now());
         }
 
@@ -408,6 +782,7 @@ impl Board {
                 if let BoardStatus::Going(start_time) = self.status {
                     if self.rest_count == self.count {
                         self.prepare_for_finish();
+                        self.clock.stop();
                         self.status = BoardStatus::Finished(start_time, Local::now());
                     }
                 }
@@ -418,6 +793,7 @@ impl Board {
             } else {
                 self.block_mut(y, x).status = BlockStatus::Open;
                 if let BoardStatus::Going(start_time) = self.status {
+                    self.clock.stop();
                     self.status = BoardStatus::Died(start_time, Local::now());
                 } else {
                     unreachable!()
@@ -426,10 +802,71 @@ impl Board {
         }
     }
 
+    pub(crate) fn apply_command(&mut self, command: &ModelCommand) {
+        self.hint_pos = None;
+        // clicks on a board that is already over are not part of the game.
+        let counted = match self.status {
+            BoardStatus::Ready | BoardStatus::Going(..) => true,
+            BoardStatus::Finished(..) | BoardStatus::Died(..) => false,
+        };
+        match *command {
+            ModelCommand::OpenBlock(y, x) => {
+                let old_block_status = self.block_status(y, x);
+                self.open_block(y, x);
+                if counted {
+                    self.click_stats.left += 1;
+                    if self.block_status(y, x) == old_block_status {
+                        self.click_stats.wasted += 1;
+                    }
+                }
+            }
+            ModelCommand::BlastBlock(y, x) => {
+                let old_rest_count = self.rest_count;
+                self.blast_block(y, x);
+                if counted {
+                    self.click_stats.chords += 1;
+                    if self.rest_count == old_rest_count {
+                        self.click_stats.wasted += 1;
+                    }
+                }
+            }
+            ModelCommand::RotateBlockState(y, x) => {
+                let old_block_status = self.block_status(y, x);
+                self.rotate_block_state(y, x);
+                let new_block_status = self.block_status(y, x);
+                if counted {
+                    self.click_stats.right += 1;
+                    if new_block_status == old_block_status {
+                        self.click_stats.wasted += 1;
+                    } else if old_block_status == BlockStatus::MarkedMine
+                        || new_block_status == BlockStatus::MarkedMine
+                    {
+                        self.click_stats.flag_toggles += 1;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
     pub(crate) fn set_allow_marks(&mut self, allow_marks: bool) {
         self.allow_marks = allow_marks;
     }
 
+    /// Whether no-guess mode found no layout that can be cleared without
+    /// guessing and this board may need a guess.
+    pub fn is_no_guess_fallback(&self) -> bool {
+        self.no_guess_fallback
+    }
+
+    pub(crate) fn set_no_guess(&mut self, no_guess: bool) {
+        self.no_guess = no_guess;
+    }
+
+    pub(crate) fn set_safe_start(&mut self, safe_start: bool) {
+        self.safe_start = safe_start;
+    }
+
     pub(crate) fn rotate_block_state(&mut self, y: usize, x: usize) {
         let idx = self.block_data_idx(y, x);
         match self.status {
@@ -457,11 +894,14 @@ impl Board {
         }
     }
 
-    pub(crate) fn block_display_kind(
+    pub fn block_display_kind(
         &self,
         pos: (usize, usize),
         focus: Option<(usize, usize, bool)>,
     ) -> BlockDisplayKind {
+        if self.paused {
+            return BlockDisplayKind::Normal;
+        }
         let (y, x) = pos;
         let board_status = self.status();
         let block_display_number = self.block_display_number(y, x);
@@ -551,11 +991,7 @@ impl Board {
         }
     }
 
-    pub(crate) fn game_button_display_kind(
-        &self,
-        pressed: bool,
-        captured: bool,
-    ) -> GameButtonDisplayKind {
+    pub fn game_button_display_kind(&self, pressed: bool, captured: bool) -> GameButtonDisplayKind {
         if pressed {
             GameButtonDisplayKind::Pushed
         } else {
@@ -574,15 +1010,29 @@ impl Board {
     }
 }
 
+const UNDO_LIMIT: usize = 256;
+
 pub struct Model {
     config: Config,
     game_mode: GameMode,
     board: Board,
+    hero_list: HeroList,
+    game_history: GameHistory,
+    pending_hero_record: Option<(model_config::BoardSettingKind, Duration)>,
+    paused_by_minimize: bool,
+    undo_list: Vec<Board>,
+    redo_list: Vec<Board>,
+    storage: Storage,
 }
 
 impl Model {
     pub fn new() -> Model {
-        let config = Config::new();
+        Model::with_storage(Storage::user_dirs())
+    }
+
+    /// A model that reads and writes its files through `storage`.
+    pub fn with_storage(storage: Storage) -> Model {
+        let config = Config::load(&storage);
 
         let game_mode = GameMode::Normal;
 
@@ -591,13 +1041,26 @@ impl Model {
             let mut board = Board::new(board_setting.y, board_setting.x, board_setting.c);
             let allow_marks = &config.allow_marks;
             board.allow_marks = allow_marks.0;
+            board.no_guess = config.no_guess.0;
+            board.safe_start = config.safe_start.0;
+            board.kind = board_setting.k;
             board
         };
 
+        let hero_list = HeroList::load(&storage);
+        let game_history = GameHistory::load(&storage);
+
         Model {
             config,
             board,
             game_mode,
+            hero_list,
+            game_history,
+            pending_hero_record: None,
+            paused_by_minimize: false,
+            undo_list: Vec::new(),
+            redo_list: Vec::new(),
+            storage,
         }
     }
 
@@ -608,6 +1071,195 @@ impl Model {
     pub fn game_mode(&self) -> GameMode {
         self.game_mode.clone()
     }
+
+    fn set_board(&mut self, board: Board) {
+        self.board = board;
+        self.undo_list.clear();
+        self.redo_list.clear();
+    }
+
+    fn apply_command_with_undo(&mut self, command: &ModelCommand) {
+        match self.board.status() {
+            BoardStatus::Ready | BoardStatus::Going(..) => {}
+            BoardStatus::Finished(..) | BoardStatus::Died(..) => return,
+        }
+        let snapshot = self.board.clone();
+        self.board.apply_command(command);
+        if self.board.is_same_progress(&snapshot) {
+            return;
+        }
+        if self.undo_list.len() == UNDO_LIMIT {
+            self.undo_list.remove(0);
+        }
+        self.undo_list.push(snapshot);
+        self.redo_list.clear();
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo_list.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo_list.is_empty()
+    }
+
+    /// Moving through the history turns the game into practice, which no
+    /// longer counts toward best times.
+    fn step_history(&mut self, forward: bool) {
+        let (from_list, to_list) = if forward {
+            (&mut self.redo_list, &mut self.undo_list)
+        } else {
+            (&mut self.undo_list, &mut self.redo_list)
+        };
+        let mut board = match from_list.pop() {
+            Some(board) => board,
+            None => return,
+        };
+        board.practice = true;
+        board.hint_pos = None;
+        self.board.practice = true;
+        self.board.hint_pos = None;
+        to_list.push(std::mem::replace(&mut self.board, board));
+    }
+
+    fn save_config(&self) {
+        if let Err(e) = self.config.save(&self.storage) {
+            log::warn!("Failed to save config: {}", e);
+        }
+    }
+
+    pub fn hero_list(&self) -> &HeroList {
+        &self.hero_list
+    }
+
+    pub fn game_history(&self) -> &GameHistory {
+        &self.game_history
+    }
+
+    /// Practice games are left out, as a lost game brought back with undo has
+    /// already been recorded.
+    fn record_game_result(&mut self, stats: &GameStats) {
+        if self.board.is_practice() {
+            return;
+        }
+        let date = match self.board.status() {
+            BoardStatus::Finished(_, end_time) | BoardStatus::Died(_, end_time) => end_time,
+            _ => return,
+        };
+        let (height, width) = self.board.size();
+        let mines = self.board.goal_mark_count();
+        let mode = match self.game_mode {
+            GameMode::BoardPredefined(..) => PlayMode::Predefined,
+            GameMode::GameRecording(..) => PlayMode::Recording,
+            _ => PlayMode::Normal,
+        };
+        let code = self.board.snapshot_mine_pos_list().map(|mine_pos| {
+            let board_saved = BoardSaved {
+                board_size: (height, width),
+                mine_pos: Rc::new(mine_pos),
+            };
+            board_saved.export_to_code(self.board.board_seed())
+        });
+        let result = GameResult {
+            date,
+            kind: self.board.kind(),
+            mode,
+            width,
+            height,
+            mines,
+            time: stats.time,
+            won: stats.won,
+            clicks: stats.clicks.total(),
+            bbbv: stats.bbbv,
+            code,
+            assisted: self.board.hint_count() > 0 || self.board.is_heatmap_used(),
+        };
+        if let Err(e) = self.game_history.append(result) {
+            log::warn!("Failed to save game statistics: {}", e);
+        }
+    }
+
+    fn check_hero_record(
+        &mut self,
+        old_status: &BoardStatus,
+    ) -> Option<model_config::BoardSettingKind> {
+        match (old_status, self.board.status()) {
+            (BoardStatus::Finished(..), _) => return None,
+            (_, BoardStatus::Finished(..)) => {}
+            _ => return None,
+        }
+        if !self.game_mode.is_normal() && !self.game_mode.is_recording() {
+            return None;
+        }
+        if self.board.hint_count() > 0 || self.board.is_heatmap_used() || self.board.is_practice() {
+            return None;
+        }
+        let board_setting = &self.config.board_setting;
+        if self.board.size() != (board_setting.y, board_setting.x)
+            || self.board.goal_mark_count() != board_setting.c
+        {
+            return None;
+        }
+        let time = self.board.elapsed_time();
+        if !self.hero_list.is_new_record(board_setting.k, time) {
+            return None;
+        }
+        self.pending_hero_record = Some((board_setting.k, time));
+        Some(board_setting.k)
+    }
+
+    pub fn playback_position(&self) -> Option<(usize, usize)> {
+        match &self.game_mode {
+            GameMode::GamePlayback(_, _, playback) => {
+                let playback = playback.borrow();
+                Some((playback.position(), playback.len()))
+            }
+            _ => None,
+        }
+    }
+
+    fn seek_playback(&mut self, idx: usize) {
+        let playback = match &self.game_mode {
+            GameMode::GamePlayback(_, _, playback) => playback.clone(),
+            _ => return,
+        };
+        let mut playback = playback.borrow_mut();
+        let mut board = self.board.renew();
+        for recorded in playback.seek(idx) {
+            board.apply_command(&recorded.command);
+        }
+        self.set_board(board);
+    }
+
+    fn update_playback_state<F: FnOnce(&mut Playback)>(&mut self, f: F) {
+        if let GameMode::GamePlayback(_, _, playback) = &self.game_mode {
+            f(&mut playback.borrow_mut());
+        }
+    }
+
+    fn record_command(&mut self, command: &ModelCommand) -> Result<(), AlertFailure> {
+        if let GameMode::GameRecording(_, recorder) = &self.game_mode {
+            // play time leaves out pauses, so the playback does not wait for them.
+            let offset = self.board.elapsed_time();
+            recorder
+                .borrow_mut()
+                .append_command(&mut self.board, offset, command)
+        } else {
+            Ok(())
+        }
+    }
+
+    fn finish_recording(&mut self) -> Result<(), AlertFailure> {
+        let result = if let GameMode::GameRecording(_, recorder) = &self.game_mode {
+            let offset = self.board.elapsed_time();
+            recorder.borrow_mut().append_finish(&mut self.board, offset)
+        } else {
+            return Ok(());
+        };
+        let board_saved = BoardSaved::import_from_board(&mut self.board);
+        self.game_mode = GameMode::BoardPredefined(board_saved);
+        result
+    }
 }
 
 #[derive(Clone, Debug)]
@@ -616,19 +1268,51 @@ pub enum ModelCommand {
 
     NewGame,
     NewGameWithBoard(model_config::BoardSetting),
+    NewGameWithSeed(u64),
 
     OpenBlock(usize, usize),
     BlastBlock(usize, usize),
     RotateBlockState(usize, usize),
 
+    Hint,
+    TogglePause,
+    SetMinimized(bool),
+    Undo,
+    Redo,
+
     ToggleAllowMarks,
+    ToggleNoGuess,
+    ToggleSafeStart,
 
     UpdateZoomRatio(model_config::ZoomRatio),
+    ToggleHeatmap,
 
     SaveMap(PathBuf),
     LoadMap(PathBuf),
+    ShowBoardCode,
+    LoadBoardCode(String),
+    ExportImage(PathBuf, model_config::ZoomRatio),
+    ExportDrawing(PathBuf, view_export::CoordinateLabels),
     RestartGame,
 
+    StartRecording(PathBuf),
+    StartPlayback(PathBuf),
+    StopRecordingOrPlayback,
+
+    UpdateHeroName(String),
+    ResetHeroList,
+    ResetStatistics,
+    ExportStatistics(PathBuf),
+
+    PlaybackTogglePause,
+    PlaybackSeek(usize),
+    PlaybackStepForward,
+    PlaybackStepBackward,
+    PlaybackFaster,
+    PlaybackSlower,
+
+    Tick,
+
     EffectNewGameButtonDown,
     EffectNewGameButtonUp,
     EffectPushBlock { x: usize, y: usize },
@@ -640,6 +1324,38 @@ pub enum ModelCommand {
     EffectUnCapture,
 }
 
+impl ModelCommand {
+    fn is_player_input(&self) -> bool {
+        matches!(
+            self,
+            ModelCommand::NewGame
+                | ModelCommand::NewGameWithBoard(..)
+                | ModelCommand::NewGameWithSeed(..)
+                | ModelCommand::OpenBlock(..)
+                | ModelCommand::BlastBlock(..)
+                | ModelCommand::RotateBlockState(..)
+                | ModelCommand::Hint
+                | ModelCommand::TogglePause
+                | ModelCommand::Undo
+                | ModelCommand::Redo
+                | ModelCommand::ToggleAllowMarks
+                | ModelCommand::ToggleNoGuess
+                | ModelCommand::ToggleSafeStart
+                | ModelCommand::SaveMap(..)
+                | ModelCommand::LoadMap(..)
+                | ModelCommand::ShowBoardCode
+                | ModelCommand::LoadBoardCode(..)
+                | ModelCommand::RestartGame
+                | ModelCommand::StartRecording(..)
+                | ModelCommand::StartPlayback(..)
+                | ModelCommand::EffectPushBlock { .. }
+                | ModelCommand::EffectPopBlock { .. }
+                | ModelCommand::EffectBlastDownBlock { .. }
+                | ModelCommand::EffectBlastUpBlock { .. }
+        )
+    }
+}
+
 impl ops::Deref for Model {
     type Target = Board;
 
@@ -654,152 +1370,1053 @@ impl ops::DerefMut for Model {
     }
 }
 
-type ModelToken<'a> = ::domino::mvc::ModelToken<'a, Model, view::View, controller::Controller>;
+struct ModelToken<'a> {
+    model: &'a mut Model,
+    view_commands: &'a mut Vec<ViewCommand>,
+}
+
+impl<'a> ModelToken<'a> {
+    fn model(&self) -> &Model {
+        self.model
+    }
+
+    fn model_mut(&mut self) -> &mut Model {
+        self.model
+    }
+
+    fn update_view_next(&mut self, command: ViewCommand) {
+        self.view_commands.push(command);
+    }
+}
 
-impl ::domino::mvc::Model<view::View, controller::Controller> for Model {
-    type Command = ModelCommand;
-    type Notification = view::ViewCommand;
+fn load_board(token: &mut ModelToken, board_saved: BoardSaved) {
+    let new_gamemode;
+    let new_board_setting;
+    {
+        let model = token.model_mut();
+        let old_size = model.board.size();
+        model.set_board(Board::new(
+            board_saved.board_size.0,
+            board_saved.board_size.1,
+            board_saved.mine_pos.len(),
+        ));
+        model.board.set_allow_marks(model.config.allow_marks.0);
+        model.fixed_mine_pos = Some(board_saved.mine_pos.clone());
+        new_board_setting = if old_size != board_saved.board_size {
+            Some(board_saved.board_setting())
+        } else {
+            None
+        };
 
-    fn process_command(mut token: ModelToken, command: ModelCommand) {
-        match command {
-            ModelCommand::Initialize => {
-                token.update_view_next(ViewCommand::Initialize);
+        model.game_mode = GameMode::BoardPredefined(board_saved);
+        new_gamemode = model.game_mode();
+    }
+    if let Some(board_setting) = new_board_setting {
+        token.update_view_next(ViewCommand::UpdateUIBoardSetting(board_setting));
+    }
+    token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+}
+
+fn stop_recording(token: &mut ModelToken) {
+    let result;
+    let new_gamemode;
+    {
+        let model = token.model_mut();
+        if !model.game_mode.is_recording() {
+            return;
+        }
+        result = model.finish_recording();
+        new_gamemode = model.game_mode();
+    }
+    match result {
+        Ok(()) => token.update_view_next(ViewCommand::AlertNotice(AlertNotice::RecordFinished)),
+        Err(failure) => token.update_view_next(ViewCommand::AlertFailure(failure)),
+    }
+    token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+}
+
+fn stop_playback(token: &mut ModelToken) {
+    let new_gamemode;
+    {
+        let model = token.model_mut();
+        let board_saved = match &model.game_mode {
+            GameMode::GamePlayback(board_saved, ..) => board_saved.clone(),
+            _ => return,
+        };
+        model.game_mode = GameMode::BoardPredefined(board_saved);
+        new_gamemode = model.game_mode();
+    }
+    token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+}
+
+fn update_playback(token: &mut ModelToken) {
+    {
+        let model = token.model_mut();
+        let playback = match &model.game_mode {
+            GameMode::GamePlayback(_, _, playback) => playback.clone(),
+            _ => return,
+        };
+        let mut playback = playback.borrow_mut();
+        while let Some(command) = playback.pop_due_command() {
+            model.board.apply_command(&command);
+        }
+        if !playback.is_finished() || playback.is_paused() {
+            return;
+        }
+        // stay on the final position so that it can still be reviewed.
+        playback.set_paused(true);
+    }
+    token.update_view_next(ViewCommand::AlertNotice(AlertNotice::PlaybackFinished));
+}
+
+fn update_recording(token: &mut ModelToken, command: &ModelCommand) {
+    let game_over;
+    {
+        let model = token.model_mut();
+        if !model.game_mode.is_recording() {
+            return;
+        }
+        if let Err(failure) = model.record_command(command) {
+            let _ = model.finish_recording();
+            let new_gamemode = model.game_mode();
+            token.update_view_next(ViewCommand::AlertFailure(failure));
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+            return;
+        }
+        game_over = matches!(
+            model.board.status(),
+            BoardStatus::Finished(..) | BoardStatus::Died(..)
+        );
+    }
+    if game_over {
+        stop_recording(token);
+    }
+}
+
+impl Model {
+    /// Applies `command` and returns the updates for the view, in order.
+    pub fn process_command(&mut self, command: ModelCommand) -> Vec<ViewCommand> {
+        let mut view_commands = Vec::new();
+        process_command(
+            ModelToken {
+                model: self,
+                view_commands: &mut view_commands,
+            },
+            command,
+        );
+        view_commands
+    }
+}
+
+fn process_command(mut token: ModelToken, command: ModelCommand) {
+    if token.model_mut().game_mode.is_playback() && command.is_player_input() {
+        return;
+    }
+
+    match command {
+        ModelCommand::Initialize => {
+            token.update_view_next(ViewCommand::Initialize);
+        }
+        ModelCommand::NewGameWithBoard(v) => {
+            stop_recording(&mut token);
+            let new_gamemode;
+            {
+                let model = token.model_mut();
+                model.config.board_setting = v.clone();
+                model.save_config();
+                model.set_board(Board::new(v.y, v.x, v.c));
+                model.board.kind = v.k;
+                model.board.set_allow_marks(model.config.allow_marks.0);
+                model.board.set_no_guess(model.config.no_guess.0);
+                model.board.set_safe_start(model.config.safe_start.0);
+                model.game_mode = GameMode::Normal;
+                new_gamemode = model.game_mode();
             }
-            ModelCommand::NewGameWithBoard(v) => {
-                {
-                    let model = token.model_mut();
-                    model.board = Board::new(v.y, v.x, v.c);
-                }
-                token.update_view_next(ViewCommand::UpdateUIBoardSetting(v.clone()));
+            token.update_view_next(ViewCommand::UpdateUIBoardSetting(v.clone()));
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::NewGameWithSeed(seed) => {
+            stop_recording(&mut token);
+            let new_gamemode;
+            {
+                let model = token.model_mut();
+                let size = model.board.size();
+                let count = model.board.goal_mark_count();
+                let kind = model.board.kind();
+                model.set_board(Board::new(size.0, size.1, count));
+                model.board.kind = kind;
+                model.board.set_seed(seed);
+                model.board.set_allow_marks(model.config.allow_marks.0);
+                model.board.set_no_guess(model.config.no_guess.0);
+                model.board.set_safe_start(model.config.safe_start.0);
+                model.game_mode = GameMode::Normal;
+                new_gamemode = model.game_mode();
             }
-            ModelCommand::NewGame => {
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::NewGame => {
+            stop_recording(&mut token);
+            let new_gamemode;
+            {
                 let model = token.model_mut();
                 let size = model.board.size();
                 let count = model.board.goal_mark_count();
-                model.board = Board::new(size.0, size.1, count);
+                let kind = model.board.kind();
+                model.set_board(Board::new(size.0, size.1, count));
+                model.board.kind = kind;
+                model.board.set_allow_marks(model.config.allow_marks.0);
+                model.board.set_no_guess(model.config.no_guess.0);
+                model.board.set_safe_start(model.config.safe_start.0);
+                model.game_mode = GameMode::Normal;
+                new_gamemode = model.game_mode();
             }
-            ModelCommand::LoadMap(path) => {
-                let new_gamemode;
-                {
-                    let board_saved = if let Some(board_saved) = BoardSaved::import_from_file(&path)
-                    {
-                        board_saved
-                    } else {
-                        token
-                            .update_view_next(ViewCommand::AlertFailure(AlertFailure::FileIOError));
-                        return;
-                    };
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::LoadMap(path) => match BoardSaved::import_from_file(&path) {
+            Ok(board_saved) => load_board(&mut token, board_saved),
+            Err(failure) => token.update_view_next(ViewCommand::AlertFailure(failure)),
+        },
+        ModelCommand::LoadBoardCode(code) => match BoardSaved::import_from_code(&code) {
+            Ok(board_saved) => load_board(&mut token, board_saved),
+            Err(failure) => token.update_view_next(ViewCommand::AlertFailure(failure)),
+        },
+        ModelCommand::ShowBoardCode => {
+            let new_gamemode;
+            let code;
+            {
+                let model = token.model_mut();
+                if !model.game_mode.is_predefined() {
+                    let board_saved = BoardSaved::import_from_board(&mut model.board);
+                    model.game_mode = GameMode::BoardPredefined(board_saved);
+                }
+                new_gamemode = model.game_mode();
+                code = new_gamemode
+                    .board_saved()
+                    .unwrap()
+                    .export_to_code(model.board.board_seed());
+            }
+            token.update_view_next(ViewCommand::ShowBoardCode(code));
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::ExportImage(path, zoom_ratio) => {
+            let result = view_export::export_png(token.model(), zoom_ratio, &path);
+            if let Err(failure) = result {
+                token.update_view_next(ViewCommand::AlertFailure(failure));
+            }
+        }
+        ModelCommand::ExportDrawing(path, labels) => {
+            let result = view_export::export_svg(&token.model().board, labels, &path);
+            if let Err(failure) = result {
+                token.update_view_next(ViewCommand::AlertFailure(failure));
+            }
+        }
+        ModelCommand::SaveMap(path) => {
+            let new_gamemode;
+            {
+                if !token.model_mut().game_mode.is_predefined() {
                     let model = token.model_mut();
-                    model.board = Board::new(
-                        board_saved.board_size.0,
-                        board_saved.board_size.1,
-                        board_saved.mine_pos.len(),
-                    );
-                    model.fixed_mine_pos = Some(board_saved.mine_pos.clone());
-
+                    let board_saved = BoardSaved::import_from_board(&mut model.board);
                     model.game_mode = GameMode::BoardPredefined(board_saved);
-                    new_gamemode = model.game_mode();
                 }
-                token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+                new_gamemode = token.model_mut().game_mode();
+                if let Err(failure) = new_gamemode.board_saved().unwrap().export_to_file(&path) {
+                    token.update_view_next(ViewCommand::AlertFailure(failure));
+                }
             }
-            ModelCommand::SaveMap(path) => {
-                let new_gamemode;
-                {
-                    if !token.model_mut().game_mode.is_predefined() {
-                        let model = token.model_mut();
-                        let board_saved = BoardSaved::import_from_board(&mut model.board);
-                        model.game_mode = GameMode::BoardPredefined(board_saved);
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::RestartGame => {
+            let new_gamemode;
+            {
+                let model = token.model_mut();
+                if !model.game_mode.is_predefined() {
+                    let board_saved = BoardSaved::import_from_board(&mut model.board);
+                    model.game_mode = GameMode::BoardPredefined(board_saved);
+                }
+                new_gamemode = model.game_mode();
+                model.set_board(model.board.renew());
+            }
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::StartRecording(path) => {
+            let new_gamemode;
+            {
+                let model = token.model_mut();
+                if model.game_mode.is_recording() || model.game_mode.is_playback() {
+                    return;
+                }
+                let start_time = Local::now();
+                let allow_marks = model.board.allow_marks;
+                match GameRecorder::create_file(&path, start_time, allow_marks) {
+                    Ok(recorder) => {
+                        model.set_board(model.board.renew());
+                        model.game_mode =
+                            GameMode::GameRecording(start_time, Rc::new(RefCell::new(recorder)));
+                        new_gamemode = model.game_mode();
                     }
-                    new_gamemode = token.model_mut().game_mode();
-                    if !new_gamemode
-                        .board_saved()
-                        .unwrap()
-                        .export_to_file(&path)
-                        .is_err()
-                    {
-                        token
-                            .update_view_next(ViewCommand::AlertFailure(AlertFailure::FileIOError));
+                    Err(failure) => {
+                        token.update_view_next(ViewCommand::AlertFailure(failure));
+                        return;
                     }
                 }
-                token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
             }
-            ModelCommand::RestartGame => {
-                let new_gamemode;
-                {
-                    let model = token.model_mut();
-                    if !model.game_mode.is_predefined() {
-                        let board_saved = BoardSaved::import_from_board(&mut model.board);
-                        model.game_mode = GameMode::BoardPredefined(board_saved);
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::StartPlayback(path) => {
+            let new_gamemode;
+            let new_board_setting;
+            {
+                if token.model_mut().game_mode.is_recording() {
+                    return;
+                }
+                let record = match GameRecord::import_from_file(&path) {
+                    Ok(record) => record,
+                    Err(failure) => {
+                        token.update_view_next(ViewCommand::AlertFailure(failure));
+                        return;
                     }
-                    new_gamemode = model.game_mode();
-                    model.board = model.board.renew();
+                };
+                let model = token.model_mut();
+                let board_saved = record.board_saved;
+                new_board_setting = if model.board.size() != board_saved.board_size {
+                    Some(board_saved.board_setting())
+                } else {
+                    None
+                };
+                model.set_board(Board::new(
+                    board_saved.board_size.0,
+                    board_saved.board_size.1,
+                    board_saved.mine_pos.len(),
+                ));
+                model.board.set_allow_marks(record.allow_marks);
+                model.fixed_mine_pos = Some(board_saved.mine_pos.clone());
+                let playback = Playback::new(record.commands);
+                model.game_mode = GameMode::GamePlayback(
+                    board_saved,
+                    record.start_time,
+                    Rc::new(RefCell::new(playback)),
+                );
+                new_gamemode = model.game_mode();
+            }
+            if let Some(board_setting) = new_board_setting {
+                token.update_view_next(ViewCommand::UpdateUIBoardSetting(board_setting));
+            }
+            token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+        }
+        ModelCommand::StopRecordingOrPlayback => {
+            stop_recording(&mut token);
+            stop_playback(&mut token);
+        }
+        ModelCommand::PlaybackTogglePause => {
+            let model = token.model_mut();
+            let restart = match model.playback_position() {
+                Some((position, len)) => position >= len,
+                None => return,
+            };
+            if restart {
+                model.seek_playback(0);
+            }
+            model.update_playback_state(|playback| {
+                let paused = playback.is_paused();
+                playback.set_paused(!paused && !restart);
+            });
+        }
+        ModelCommand::PlaybackSeek(idx) => {
+            let model = token.model_mut();
+            model.seek_playback(idx);
+        }
+        ModelCommand::PlaybackStepForward | ModelCommand::PlaybackStepBackward => {
+            let model = token.model_mut();
+            let position = match model.playback_position() {
+                Some((position, _)) => position,
+                None => return,
+            };
+            model.update_playback_state(|playback| playback.set_paused(true));
+            let position = match command {
+                ModelCommand::PlaybackStepForward => position + 1,
+                _ => position.saturating_sub(1),
+            };
+            model.seek_playback(position);
+        }
+        ModelCommand::PlaybackFaster => {
+            let model = token.model_mut();
+            model.update_playback_state(|playback| playback.change_speed(1));
+        }
+        ModelCommand::PlaybackSlower => {
+            let model = token.model_mut();
+            model.update_playback_state(|playback| playback.change_speed(-1));
+        }
+        ModelCommand::Tick => {
+            update_playback(&mut token);
+        }
+        ModelCommand::OpenBlock(..)
+        | ModelCommand::BlastBlock(..)
+        | ModelCommand::RotateBlockState(..) => {
+            // a click on a paused board only resumes the game.
+            if token.model().board.is_paused() {
+                token.model_mut().board.set_paused(false);
+                return;
+            }
+            let new_hero_record;
+            let finished_notice;
+            let game_summary;
+            let no_guess_fallback;
+            {
+                let model = token.model_mut();
+                let old_status = model.board.status();
+                if model.config.show_heatmap.0 {
+                    model.board.set_heatmap_used();
+                }
+                model.apply_command_with_undo(&command);
+                no_guess_fallback =
+                    old_status == BoardStatus::Ready && model.board.is_no_guess_fallback();
+                new_hero_record = model.check_hero_record(&old_status);
+                game_summary = match old_status {
+                    BoardStatus::Ready | BoardStatus::Going(..) => model.board.game_stats(),
+                    BoardStatus::Finished(..) | BoardStatus::Died(..) => None,
+                };
+                if let Some(stats) = &game_summary {
+                    model.record_game_result(stats);
                 }
-                token.update_view_next(ViewCommand::UpdateUIGameMode(new_gamemode));
+                finished_notice = match (old_status, model.board.status()) {
+                    (BoardStatus::Finished(..), _) => None,
+                    (_, BoardStatus::Finished(..)) if model.board.hint_count() > 0 => {
+                        Some(AlertNotice::FinishedWithHints(model.board.hint_count()))
+                    }
+                    (_, BoardStatus::Finished(..)) if model.board.is_heatmap_used() => {
+                        Some(AlertNotice::FinishedWithHeatmap)
+                    }
+                    (_, BoardStatus::Finished(..)) if model.board.is_practice() => {
+                        Some(AlertNotice::FinishedAsPractice)
+                    }
+                    _ => None,
+                };
             }
-            ModelCommand::OpenBlock(y, x) => {
+            update_recording(&mut token, &command);
+            if no_guess_fallback {
+                token.update_view_next(ViewCommand::AlertNotice(AlertNotice::NoGuessFallback));
+            }
+            if let Some(stats) = game_summary {
+                token.update_view_next(ViewCommand::AlertNotice(AlertNotice::GameSummary(stats)));
+            }
+            if let Some(kind) = new_hero_record {
+                token.update_view_next(ViewCommand::PromptHeroName(kind));
+            }
+            if let Some(notice) = finished_notice {
+                token.update_view_next(ViewCommand::AlertNotice(notice));
+            }
+        }
+        ModelCommand::Undo | ModelCommand::Redo => {
+            let forward = matches!(command, ModelCommand::Redo);
+            let possible = {
+                let model = token.model();
+                if forward {
+                    model.can_redo()
+                } else {
+                    model.can_undo()
+                }
+            };
+            if possible {
+                // a record file has no way to express going back in time.
+                stop_recording(&mut token);
+                token.model_mut().step_history(forward);
+            }
+        }
+        ModelCommand::TogglePause => {
+            let model = token.model_mut();
+            let paused = model.board.is_paused();
+            model.board.set_paused(!paused);
+        }
+        ModelCommand::SetMinimized(minimized) => {
+            let model = token.model_mut();
+            if minimized {
+                let was_paused = model.board.is_paused();
+                model.board.set_paused(true);
+                model.paused_by_minimize = !was_paused && model.board.is_paused();
+            } else if model.paused_by_minimize {
+                // a restore also follows ordinary resizes, and a game the
+                // player paused stays paused.
+                model.paused_by_minimize = false;
+                model.board.set_paused(false);
+            }
+        }
+        ModelCommand::Hint => {
+            token.model_mut().board.show_hint();
+        }
+        ModelCommand::UpdateHeroName(name) => {
+            let result;
+            {
                 let model = token.model_mut();
-                model.open_block(y, x);
+                let (kind, time) = match model.pending_hero_record.take() {
+                    Some(pending) => pending,
+                    None => return,
+                };
+                let name = match name.trim() {
+                    "" => HeroList::DEFAULT_NAME.to_owned(),
+                    name => name.to_owned(),
+                };
+                model.hero_list.update(kind, HeroRecord { name, time });
+                result = model.hero_list.save(&model.storage);
             }
-            ModelCommand::BlastBlock(y, x) => {
+            if let Err(failure) = result {
+                token.update_view_next(ViewCommand::AlertFailure(failure));
+            }
+        }
+        ModelCommand::ResetHeroList => {
+            let result;
+            {
                 let model = token.model_mut();
-                model.blast_block(y, x);
+                model.hero_list.reset();
+                result = model.hero_list.save(&model.storage);
             }
-            ModelCommand::RotateBlockState(y, x) => {
+            if let Err(failure) = result {
+                token.update_view_next(ViewCommand::AlertFailure(failure));
+            }
+        }
+        ModelCommand::ResetStatistics => {
+            let result = token.model_mut().game_history.reset();
+            if let Err(failure) = result {
+                token.update_view_next(ViewCommand::AlertFailure(failure));
+            }
+        }
+        ModelCommand::ExportStatistics(path) => {
+            let result = token.model().game_history.export_to_file(&path);
+            if let Err(failure) = result {
+                token.update_view_next(ViewCommand::AlertFailure(failure));
+            }
+        }
+        ModelCommand::ToggleAllowMarks => {
+            let new_state;
+            {
                 let model = token.model_mut();
-                model.rotate_block_state(y, x);
+                new_state = !model.config.allow_marks.0;
+                model.board.set_allow_marks(new_state);
+                model.config.allow_marks = model_config::AllowMarks(new_state);
+                model.save_config();
             }
-            ModelCommand::ToggleAllowMarks => {
-                let new_state;
-                {
-                    let model = token.model_mut();
-                    new_state = !model.config.allow_marks.0;
-                    model.board.set_allow_marks(new_state);
-                    model.config.allow_marks = model_config::AllowMarks(new_state);
-                }
-                token.update_view_next(ViewCommand::UpdateUIAllowMarks(model_config::AllowMarks(
-                    new_state,
-                )));
+            token.update_view_next(ViewCommand::UpdateUIAllowMarks(model_config::AllowMarks(
+                new_state,
+            )));
+        }
+        ModelCommand::ToggleNoGuess => {
+            let new_state;
+            {
+                let model = token.model_mut();
+                new_state = !model.config.no_guess.0;
+                model.board.set_no_guess(new_state);
+                model.config.no_guess = model_config::NoGuess(new_state);
+                model.save_config();
             }
-            ModelCommand::UpdateZoomRatio(r) => {
-                {
-                    let model = token.model_mut();
-                    model.config.zoom_ratio = r;
-                }
-                token.update_view_next(ViewCommand::UpdateZoomRatio(r));
-                token.update_view_next(ViewCommand::UpdateUIZoomRatio(r));
+            token.update_view_next(ViewCommand::UpdateUINoGuess(model_config::NoGuess(
+                new_state,
+            )));
+        }
+        ModelCommand::ToggleSafeStart => {
+            let new_state;
+            {
+                let model = token.model_mut();
+                new_state = !model.config.safe_start.0;
+                model.board.set_safe_start(new_state);
+                model.config.safe_start = model_config::SafeStart(new_state);
+                model.save_config();
             }
-            ModelCommand::EffectNewGameButtonDown => {
-                token.update_view_next(ViewCommand::SetButtonPressed(true));
+            token.update_view_next(ViewCommand::UpdateUISafeStart(model_config::SafeStart(
+                new_state,
+            )));
+        }
+        ModelCommand::ToggleHeatmap => {
+            let new_state;
+            {
+                let model = token.model_mut();
+                new_state = !model.config.show_heatmap.0;
+                if new_state {
+                    model.board.set_heatmap_used();
+                }
+                model.config.show_heatmap = model_config::ShowHeatmap(new_state);
+                model.save_config();
             }
-            ModelCommand::EffectNewGameButtonUp => {
-                token.update_view_next(ViewCommand::SetButtonPressed(false));
+            token.update_view_next(ViewCommand::UpdateUIHeatmap(model_config::ShowHeatmap(
+                new_state,
+            )));
+        }
+        ModelCommand::UpdateZoomRatio(r) => {
+            {
+                let model = token.model_mut();
+                model.config.zoom_ratio = r;
+                model.save_config();
             }
-            ModelCommand::EffectPushBlock { y, x } => {
-                token.update_view_next(ViewCommand::SetBlockPressed(y, x, false));
+            token.update_view_next(ViewCommand::UpdateZoomRatio(r));
+            token.update_view_next(ViewCommand::UpdateUIZoomRatio(r));
+        }
+        ModelCommand::EffectNewGameButtonDown => {
+            token.update_view_next(ViewCommand::SetButtonPressed(true));
+        }
+        ModelCommand::EffectNewGameButtonUp => {
+            token.update_view_next(ViewCommand::SetButtonPressed(false));
+        }
+        ModelCommand::EffectPushBlock { y, x } => {
+            token.update_view_next(ViewCommand::SetBlockPressed(y, x, false));
+        }
+        ModelCommand::EffectPopBlock { y, x } => {
+            token.update_view_next(ViewCommand::UnsetBlockPressed(y, x, false));
+        }
+        ModelCommand::EffectBlastDownBlock { y, x } => {
+            token.update_view_next(ViewCommand::SetBlockPressed(y, x, true));
+        }
+        ModelCommand::EffectBlastUpBlock { y, x } => {
+            token.update_view_next(ViewCommand::UnsetBlockPressed(y, x, true));
+        }
+        ModelCommand::EffectCapture => {
+            token.update_view_next(ViewCommand::SetCapture);
+        }
+        ModelCommand::EffectUnCapture => {
+            token.update_view_next(ViewCommand::ReleaseCapture);
+        }
+    }
+
+    token.update_view_next(ViewCommand::Refresh);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_storage::ScratchDir;
+
+    fn new_board(seed: u64) -> Board {
+        let mut board = Board::new(10, 11, 10);
+        board.set_seed(seed);
+        board
+    }
+
+    /// Model keeping its files in a fresh directory, which lives as long as
+    /// the returned `ScratchDir`.
+    fn scratch_model(name: &str) -> (Model, ScratchDir) {
+        let dir = ScratchDir::new(name);
+        (Model::with_storage(dir.storage()), dir)
+    }
+
+    /// Opens every block without a mine on a board with fixed mines.
+    fn win(model: &mut Model) -> Vec<ViewCommand> {
+        let mine_pos = model.board.fixed_mine_pos_list().unwrap().clone();
+        let (h, w) = model.board.size();
+        let mut view_commands = Vec::new();
+        for y in 0..h {
+            for x in 0..w {
+                if !mine_pos.contains(&(y * w + x))
+                    && model.board.block_status(y, x) == BlockStatus::Normal
+                {
+                    view_commands.extend(model.process_command(ModelCommand::OpenBlock(y, x)));
+                }
             }
-            ModelCommand::EffectPopBlock { y, x } => {
-                token.update_view_next(ViewCommand::UnsetBlockPressed(y, x, false));
+        }
+        view_commands
+    }
+
+    fn is_finished(board: &Board) -> bool {
+        matches!(board.status(), BoardStatus::Finished(..))
+    }
+
+    #[test]
+    fn first_click_is_never_a_mine() {
+        for seed in 0..100 {
+            let mut board = new_board(seed);
+            board.apply_command(&ModelCommand::OpenBlock(4, 5));
+            assert!(!board.block(4, 5).has_mine);
+            assert_eq!(board.block_status(4, 5), BlockStatus::Open);
+            assert_eq!(board.snapshot_mine_pos_list().unwrap().len(), 10);
+        }
+    }
+
+    #[test]
+    fn safe_start_opens_a_region() {
+        for seed in 0..100 {
+            let mut board = new_board(seed);
+            board.set_safe_start(true);
+            board.apply_command(&ModelCommand::OpenBlock(0, 0));
+            assert_eq!(board.block_display_number(0, 0), Some(0));
+        }
+    }
+
+    #[test]
+    fn same_seed_same_layout() {
+        let mut a = new_board(2024);
+        let mut b = new_board(2024);
+        a.apply_command(&ModelCommand::OpenBlock(3, 3));
+        b.apply_command(&ModelCommand::OpenBlock(3, 3));
+        assert_eq!(a.snapshot_mine_pos_list(), b.snapshot_mine_pos_list());
+    }
+
+    #[test]
+    fn board_without_mines_is_one_click() {
+        let mut board = Board::new(10, 11, 0);
+        board.update_fixed_mine_pos_list(Some(Rc::new(Vec::new())));
+        board.apply_command(&ModelCommand::OpenBlock(5, 5));
+        assert!(is_finished(&board));
+        assert_eq!(board.bbbv(), Some(1));
+        assert_eq!(board.click_stats().total(), 1);
+    }
+
+    #[test]
+    fn bbbv_clicks_clear_the_board() {
+        for seed in 0..50 {
+            let mut board = new_board(seed);
+            board.set_safe_start(true);
+            assert_eq!(board.bbbv(), None);
+            board.apply_command(&ModelCommand::OpenBlock(4, 5));
+            let bbbv = board.bbbv().unwrap();
+            let (h, w) = board.size();
+            // openings first, then the numbers no opening has revealed.
+            for &zeros_only in &[true, false] {
+                for y in 0..h {
+                    for x in 0..w {
+                        let number = board.block_display_number(y, x);
+                        if board.block_status(y, x) == BlockStatus::Normal
+                            && number.is_some()
+                            && (number == Some(0) || !zeros_only)
+                        {
+                            board.apply_command(&ModelCommand::OpenBlock(y, x));
+                        }
+                    }
+                }
             }
-            ModelCommand::EffectBlastDownBlock { y, x } => {
-                token.update_view_next(ViewCommand::SetBlockPressed(y, x, true));
+            assert!(is_finished(&board));
+            assert_eq!(board.click_stats().left, bbbv);
+            assert_eq!(board.click_stats().wasted, 0);
+        }
+    }
+
+    #[test]
+    fn clicks_on_open_blocks_are_wasted() {
+        let mut board = new_board(7);
+        board.set_safe_start(true);
+        board.apply_command(&ModelCommand::OpenBlock(4, 5));
+        board.apply_command(&ModelCommand::OpenBlock(4, 5));
+        board.apply_command(&ModelCommand::RotateBlockState(4, 5));
+        let stats = board.click_stats();
+        assert_eq!((stats.left, stats.right, stats.wasted), (2, 1, 2));
+    }
+
+    #[test]
+    fn no_guess_board_is_solvable_from_the_first_click() {
+        for seed in 0..20 {
+            let mut board = new_board(seed);
+            board.set_no_guess(true);
+            board.apply_command(&ModelCommand::OpenBlock(4, 5));
+            let mine_pos = board.snapshot_mine_pos_list().unwrap();
+            let start = board.block_data_idx(4, 5);
+            assert!(model_solver::is_solvable_without_guess(
+                board.size(),
+                &mine_pos,
+                start
+            ));
+        }
+    }
+
+    #[test]
+    fn no_guess_board_only_depends_on_the_seed() {
+        let mut a = new_board(2024);
+        let mut b = new_board(2024);
+        a.set_no_guess(true);
+        b.set_no_guess(true);
+        a.apply_command(&ModelCommand::OpenBlock(0, 0));
+        b.apply_command(&ModelCommand::OpenBlock(0, 0));
+        assert_eq!(a.snapshot_mine_pos_list(), b.snapshot_mine_pos_list());
+    }
+
+    #[test]
+    fn no_guess_falls_back_on_a_dense_board() {
+        let (mut model, _dir) = scratch_model("no-guess-fallback");
+        model.board = Board::new(10, 11, 100);
+        model.board.set_seed(3);
+        model.board.set_no_guess(true);
+        let view_commands = model.process_command(ModelCommand::OpenBlock(4, 5));
+        assert!(model.board.is_no_guess_fallback());
+        assert!(!model.board.block(4, 5).has_mine);
+        assert!(view_commands
+            .iter()
+            .any(|c| matches!(c, ViewCommand::AlertNotice(AlertNotice::NoGuessFallback))));
+
+        let mut board = new_board(3);
+        board.set_no_guess(true);
+        board.apply_command(&ModelCommand::OpenBlock(4, 5));
+        assert!(!board.is_no_guess_fallback());
+    }
+
+    #[test]
+    fn heatmap_games_are_not_hero_records() {
+        for &heatmap in &[false, true] {
+            let (mut model, _dir) = scratch_model("heatmap");
+            model
+                .board
+                .update_fixed_mine_pos_list(Some(Rc::new((0..10).collect())));
+            if heatmap {
+                model.process_command(ModelCommand::ToggleHeatmap);
             }
-            ModelCommand::EffectBlastUpBlock { y, x } => {
-                token.update_view_next(ViewCommand::UnsetBlockPressed(y, x, true));
+            let view_commands = win(&mut model);
+            assert!(is_finished(&model.board));
+            assert_eq!(model.board.is_heatmap_used(), heatmap);
+            let prompted = view_commands
+                .iter()
+                .any(|command| matches!(command, ViewCommand::PromptHeroName(..)));
+            assert_eq!(prompted, !heatmap);
+        }
+    }
+
+    #[test]
+    fn hint_on_a_ready_board_is_the_center() {
+        let mut board = new_board(3);
+        board.show_hint();
+        assert_eq!(board.hint_pos(), Some((5, 5)));
+        board.show_hint();
+        assert_eq!(board.hint_count(), 1);
+        board.apply_command(&ModelCommand::OpenBlock(5, 5));
+        assert_eq!(board.hint_pos(), None);
+        assert!(!board.block(5, 5).has_mine);
+    }
+
+    #[test]
+    fn hint_points_at_a_deducible_block() {
+        let mut deduced = 0;
+        for seed in 0..20 {
+            let mut board = new_board(seed);
+            board.apply_command(&ModelCommand::OpenBlock(4, 5));
+            let grid = SolverGrid::from_board(&board);
+            let safe = model_solver::deduce(&grid).safe;
+            if safe.is_empty() {
+                continue;
             }
-            ModelCommand::EffectCapture => {
-                token.update_view_next(ViewCommand::SetCapture);
+            deduced += 1;
+            board.show_hint();
+            let (y, x) = board.hint_pos().unwrap();
+            assert!(safe.contains(&board.block_data_idx(y, x)));
+            board.apply_command(&ModelCommand::OpenBlock(y, x));
+            assert_eq!(board.block_status(y, x), BlockStatus::Open);
+            assert!(!board.block(y, x).has_mine);
+        }
+        assert!(deduced > 0);
+    }
+
+    #[test]
+    fn hint_on_a_fixed_layout_avoids_mines() {
+        let center = 5 * 11 + 5;
+        for seed in 0..20 {
+            let mut mine_pos = model_random::pick_mine_pos_list(seed, 110, 19, &[center]);
+            mine_pos.push(center);
+            let mut board = Board::new(10, 11, 20);
+            board.update_fixed_mine_pos_list(Some(Rc::new(mine_pos)));
+            board.show_hint();
+            let (y, x) = board.hint_pos().unwrap();
+            board.apply_command(&ModelCommand::OpenBlock(y, x));
+            assert_eq!(board.block_status(y, x), BlockStatus::Open);
+            assert!(!board.block(y, x).has_mine);
+        }
+    }
+
+    #[test]
+    fn hint_ignores_wrong_flags() {
+        let mut misled = 0;
+        for seed in 0..20 {
+            let mut board = new_board(seed);
+            board.apply_command(&ModelCommand::OpenBlock(4, 5));
+            let (h, w) = board.size();
+            for y in 0..h {
+                for x in 0..w {
+                    if board.block_status(y, x) == BlockStatus::Normal
+                        && !board.block(y, x).has_mine
+                    {
+                        board.apply_command(&ModelCommand::RotateBlockState(y, x));
+                    }
+                }
             }
-            ModelCommand::EffectUnCapture => {
-                token.update_view_next(ViewCommand::ReleaseCapture);
+            let trusted = model_solver::deduce(&SolverGrid::from_board(&board));
+            if trusted.safe.iter().any(|&idx| board.blocks[idx].has_mine) {
+                misled += 1;
             }
+            board.show_hint();
+            let (y, x) = board.hint_pos().unwrap();
+            assert!(!board.block(y, x).has_mine);
+        }
+        assert!(misled > 0);
+    }
+
+    /// A model in `name`'s scratch directory playing `new_board(7)`, with the
+    /// first block opened.
+    fn started_model(name: &str) -> (Model, ScratchDir) {
+        let (mut model, dir) = scratch_model(name);
+        model.board = new_board(7);
+        model.process_command(ModelCommand::OpenBlock(4, 5));
+        (model, dir)
+    }
+
+    fn normal_block(board: &Board, has_mine: bool) -> (usize, usize) {
+        let (h, w) = board.size();
+        (0..h)
+            .flat_map(|y| (0..w).map(move |x| (y, x)))
+            .find(|&(y, x)| {
+                board.block_status(y, x) == BlockStatus::Normal
+                    && board.block(y, x).has_mine == has_mine
+            })
+            .unwrap()
+    }
+
+    #[test]
+    fn undo_takes_back_a_mine_hit() {
+        let (mut model, _dir) = started_model("undo-mine");
+        let (y, x) = normal_block(&model.board, true);
+        model.process_command(ModelCommand::OpenBlock(y, x));
+        assert!(matches!(model.board.status(), BoardStatus::Died(..)));
+
+        model.process_command(ModelCommand::Undo);
+        assert!(matches!(model.board.status(), BoardStatus::Going(..)));
+        assert_eq!(model.board.block_status(y, x), BlockStatus::Normal);
+        assert!(model.board.is_practice());
+        assert!(model.can_redo());
+
+        model.process_command(ModelCommand::Redo);
+        assert!(matches!(model.board.status(), BoardStatus::Died(..)));
+        assert!(!model.can_redo());
+    }
+
+    #[test]
+    fn new_move_drops_the_redo_history() {
+        let (mut model, _dir) = started_model("undo-redo");
+        let (y, x) = normal_block(&model.board, false);
+        model.process_command(ModelCommand::RotateBlockState(y, x));
+        model.process_command(ModelCommand::Undo);
+        assert!(model.can_redo());
+
+        model.process_command(ModelCommand::OpenBlock(y, x));
+        assert!(!model.can_redo());
+        model.process_command(ModelCommand::Redo);
+        assert_eq!(model.board.block_status(y, x), BlockStatus::Open);
+    }
+
+    #[test]
+    fn undo_history_is_capped() {
+        let (mut model, _dir) = started_model("undo-limit");
+        let (y, x) = normal_block(&model.board, false);
+        let moves = UNDO_LIMIT + 43;
+        for _ in 0..moves {
+            model.process_command(ModelCommand::RotateBlockState(y, x));
+        }
+        assert_eq!(model.undo_list.len(), UNDO_LIMIT);
+
+        for _ in 0..UNDO_LIMIT {
+            assert!(model.can_undo());
+            model.process_command(ModelCommand::Undo);
         }
+        assert!(!model.can_undo());
+        assert!(matches!(model.board.status(), BoardStatus::Going(..)));
+        // the opening click and the first rotations fell out of the history;
+        // marks cycle through flag, question mark and back every three steps.
+        let expected = match (moves - UNDO_LIMIT) % 3 {
+            0 => BlockStatus::Normal,
+            1 => BlockStatus::MarkedMine,
+            _ => BlockStatus::MarkedQuestionable,
+        };
+        assert_eq!(model.board.block_status(y, x), expected);
+    }
+
+    #[test]
+    fn results_keep_the_board_kind_and_assistance() {
+        let (mut model, dir) = scratch_model("results");
+        let setting = model_config::BoardSetting::new_custom(11, 10, 10);
+        model.process_command(ModelCommand::NewGameWithBoard(setting));
+        model
+            .board
+            .update_fixed_mine_pos_list(Some(Rc::new((0..10).collect())));
+        model.process_command(ModelCommand::Hint);
+        win(&mut model);
+
+        let history = GameHistory::load(&dir.storage());
+        let result = &history.results()[0];
+        assert_eq!(history.results().len(), 1);
+        assert_eq!(result.kind, model_config::BoardSettingKind::Custom);
+        assert_eq!(result.mode, PlayMode::Normal);
+        assert!(result.won);
+        assert!(result.assisted);
+    }
+
+    thread_local! {
+        static FAKE_NOW: Cell<Option<Instant>> = Cell::new(None);
+    }
+
+    /// Time source for tests that only moves on `advance_fake_now`.
+    fn fake_now() -> Instant {
+        FAKE_NOW.with(|now| match now.get() {
+            Some(time) => time,
+            None => {
+                let time = Instant::now();
+                now.set(Some(time));
+                time
+            }
+        })
+    }
+
+    fn advance_fake_now(by: Duration) {
+        let time = fake_now() + by;
+        FAKE_NOW.with(|now| now.set(Some(time)));
+    }
+
+    #[test]
+    fn game_clock_stands_still_while_paused() {
+        let mut board = new_board(1);
+        board.set_time_source(fake_now);
+        board.apply_command(&ModelCommand::OpenBlock(4, 5));
+        assert!(matches!(board.status(), BoardStatus::Going(..)));
+        advance_fake_now(Duration::from_millis(20));
+        board.set_paused(true);
+        assert_eq!(board.elapsed_time(), Duration::from_millis(20));
+        advance_fake_now(Duration::from_secs(30));
+        assert_eq!(board.elapsed_time(), Duration::from_millis(20));
+
+        board.set_paused(false);
+        advance_fake_now(Duration::from_millis(30));
+        assert_eq!(board.elapsed_time(), Duration::from_millis(50));
+    }
 
-        token.update_view_next(ViewCommand::Refresh);
+    #[test]
+    fn recorded_offsets_leave_out_pauses() {
+        let (mut model, dir) = scratch_model("record");
+        let path = dir.path().join("game.cmr");
+        model.board = new_board(7);
+        model.board.set_time_source(fake_now);
+        model.process_command(ModelCommand::StartRecording(path.clone()));
+        model.process_command(ModelCommand::OpenBlock(4, 5));
+        advance_fake_now(Duration::from_millis(20));
+        model.process_command(ModelCommand::TogglePause);
+        advance_fake_now(Duration::from_secs(30));
+        model.process_command(ModelCommand::TogglePause);
+        advance_fake_now(Duration::from_millis(5));
+        let (y, x) = normal_block(&model.board, true);
+        model.process_command(ModelCommand::RotateBlockState(y, x));
+        model.process_command(ModelCommand::StopRecordingOrPlayback);
+
+        let record = GameRecord::import_from_file(&path).unwrap();
+        let offsets = record
+            .commands
+            .iter()
+            .map(|recorded| recorded.offset)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            offsets,
+            [Duration::from_millis(0), Duration::from_millis(25)]
+        );
     }
 
-    fn translate_controller_notification(
-        controller_notification: ModelCommand,
-    ) -> Option<Self::Command> {
-        Some(controller_notification)
+    #[test]
+    fn restoring_only_resumes_a_game_paused_by_minimizing() {
+        let (mut model, _dir) = started_model("minimize");
+        model.process_command(ModelCommand::SetMinimized(true));
+        assert!(model.board.is_paused());
+        model.process_command(ModelCommand::SetMinimized(false));
+        assert!(!model.board.is_paused());
+
+        model.process_command(ModelCommand::TogglePause);
+        model.process_command(ModelCommand::SetMinimized(false));
+        assert!(model.board.is_paused());
+        model.process_command(ModelCommand::SetMinimized(true));
+        model.process_command(ModelCommand::SetMinimized(false));
+        assert!(model.board.is_paused());
     }
 }
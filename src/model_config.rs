@@ -1,5 +1,6 @@
-use std::cell::RefCell;
-use std::rc::Rc;
+use crate::model_storage::{KeyValueFile, Storage};
+use std::io;
+use std::path::Path;
 
 #[derive(Copy, Clone, PartialEq, Debug)]
 pub enum BoardSettingKind {
@@ -38,9 +39,9 @@ impl BoardSetting {
     };
 
     pub fn new_custom(mut x: usize, mut y: usize, mut c: usize) -> Self {
-        x = Ord::clamp(Self::EASY.x, x, Self::HARD.x);
-        y = Ord::clamp(Self::EASY.y, y, Self::HARD.y);
-        c = Ord::clamp(Self::EASY.c, c, (x - 1) * (y - 1));
+        x = x.clamp(Self::EASY.x, Self::HARD.x);
+        y = y.clamp(Self::EASY.y, Self::HARD.y);
+        c = c.clamp(Self::EASY.c, (x - 1) * (y - 1));
         BoardSetting {
             x,
             y,
@@ -65,6 +66,15 @@ impl Default for AllowMarks {
     }
 }
 
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct NoGuess(pub bool);
+
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct SafeStart(pub bool);
+
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct ShowHeatmap(pub bool);
+
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum ZoomRatio {
     Zoom1x,
@@ -82,11 +92,154 @@ impl Default for ZoomRatio {
 pub struct Config {
     pub board_setting: BoardSetting,
     pub allow_marks: AllowMarks,
+    pub no_guess: NoGuess,
+    pub safe_start: SafeStart,
     pub zoom_ratio: ZoomRatio,
+    pub show_heatmap: ShowHeatmap,
 }
 
 impl Config {
+    const FILE_NAME: &'static str = "config.ini";
+
     pub fn new() -> Self {
         Default::default()
     }
+
+    pub fn load(storage: &Storage) -> Self {
+        match storage.config_file(Self::FILE_NAME) {
+            Some(path) => Self::load_from(&path),
+            None => Config::new(),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let file = KeyValueFile::load(path);
+
+        let board_setting = match file.get("board.kind") {
+            Some("easy") => BoardSetting::EASY,
+            Some("normal") => BoardSetting::NORMAL,
+            Some("hard") => BoardSetting::HARD,
+            Some("custom") => match (
+                file.get_parsed("board.width"),
+                file.get_parsed("board.height"),
+                file.get_parsed("board.mines"),
+            ) {
+                (Some(x), Some(y), Some(c)) => BoardSetting::new_custom(x, y, c),
+                _ => BoardSetting::default(),
+            },
+            _ => BoardSetting::default(),
+        };
+        let allow_marks = file
+            .get_parsed("allow_marks")
+            .map(AllowMarks)
+            .unwrap_or_default();
+        let no_guess = file.get_parsed("no_guess").map(NoGuess).unwrap_or_default();
+        let safe_start = file
+            .get_parsed("safe_start")
+            .map(SafeStart)
+            .unwrap_or_default();
+        let show_heatmap = file
+            .get_parsed("show_heatmap")
+            .map(ShowHeatmap)
+            .unwrap_or_default();
+        let zoom_ratio = match file.get_parsed::<u8>("zoom_ratio") {
+            Some(1) => ZoomRatio::Zoom1x,
+            Some(2) => ZoomRatio::Zoom2x,
+            Some(3) => ZoomRatio::Zoom3x,
+            _ => ZoomRatio::default(),
+        };
+
+        Config {
+            board_setting,
+            allow_marks,
+            no_guess,
+            safe_start,
+            zoom_ratio,
+            show_heatmap,
+        }
+    }
+
+    pub fn save(&self, storage: &Storage) -> io::Result<()> {
+        let path = storage
+            .config_file(Self::FILE_NAME)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no config directory"))?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        // entries written by other versions are kept as they are.
+        let mut file = KeyValueFile::load(path);
+
+        let board_kind = match self.board_setting.k {
+            BoardSettingKind::Easy => "easy",
+            BoardSettingKind::Normal => "normal",
+            BoardSettingKind::Hard => "hard",
+            BoardSettingKind::Custom => "custom",
+        };
+        file.set("board.kind", board_kind);
+        file.set("board.width", self.board_setting.x);
+        file.set("board.height", self.board_setting.y);
+        file.set("board.mines", self.board_setting.c);
+        file.set("allow_marks", self.allow_marks.0);
+        file.set("no_guess", self.no_guess.0);
+        file.set("safe_start", self.safe_start.0);
+        let zoom_ratio = match self.zoom_ratio {
+            ZoomRatio::Zoom1x => 1,
+            ZoomRatio::Zoom2x => 2,
+            ZoomRatio::Zoom3x => 3,
+        };
+        file.set("zoom_ratio", zoom_ratio);
+        file.set("show_heatmap", self.show_heatmap.0);
+
+        file.save(path)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_storage::ScratchDir;
+
+    #[test]
+    fn custom_board_is_clamped() {
+        let setting = BoardSetting::new_custom(1, 100, 10000);
+        assert_eq!(setting.x, BoardSetting::EASY.x);
+        assert_eq!(setting.y, BoardSetting::HARD.y);
+        assert_eq!(setting.c, (setting.x - 1) * (setting.y - 1));
+        assert_eq!(setting.k, BoardSettingKind::Custom);
+
+        let setting = BoardSetting::new_custom(1000, 0, 20);
+        assert_eq!(setting.x, BoardSetting::HARD.x);
+        assert_eq!(setting.y, BoardSetting::EASY.y);
+        assert_eq!(setting.c, 20);
+
+        let setting = BoardSetting::new_custom(30, 12, 0);
+        assert_eq!((setting.x, setting.y), (30, 12));
+        assert_eq!(setting.c, BoardSetting::EASY.c);
+    }
+
+    #[test]
+    fn config_file_roundtrip() {
+        let dir = ScratchDir::new("config");
+        let path = dir.path().join(Config::FILE_NAME);
+        std::fs::write(&path, "future.key=kept\nzoom_ratio=7\n").unwrap();
+
+        let mut config = Config::load_from(&path);
+        assert_eq!(config.zoom_ratio, ZoomRatio::default());
+        config.board_setting = BoardSetting::new_custom(30, 12, 40);
+        config.allow_marks = AllowMarks(false);
+        config.safe_start = SafeStart(true);
+        config.zoom_ratio = ZoomRatio::Zoom3x;
+        config.save_to(&path).unwrap();
+
+        let loaded = Config::load_from(&path);
+        let board = &loaded.board_setting;
+        assert_eq!((board.x, board.y, board.c), (30, 12, 40));
+        assert_eq!(board.k, BoardSettingKind::Custom);
+        assert_eq!(loaded.allow_marks, AllowMarks(false));
+        assert_eq!(loaded.no_guess, NoGuess(false));
+        assert_eq!(loaded.safe_start, SafeStart(true));
+        assert_eq!(loaded.zoom_ratio, ZoomRatio::Zoom3x);
+        assert_eq!(KeyValueFile::load(&path).get("future.key"), Some("kept"));
+    }
 }
@@ -1,12 +1,17 @@
 #![allow(unused_imports, unreachable_code, unused_variables, dead_code)]
 
-use crate::model::Board;
 use crate::model::ModelCommand;
-use chrono::{DateTime, Local};
+use crate::model::{Board, BoardSeed, BoardStatus};
+use crate::model_config::{BoardSetting, BoardSettingKind};
+use crate::view_command::AlertFailure;
+use chrono::{DateTime, Local, TimeZone};
 use std::cell::RefCell;
+use std::collections::BTreeSet;
 use std::fs::File;
+use std::io::{self, BufReader, BufWriter, Read, Write};
 use std::path::Path;
 use std::rc::Rc;
+use std::time::{Duration, Instant};
 
 #[derive(Clone, Debug)]
 pub struct BoardSaved {
@@ -34,21 +39,636 @@ impl BoardSaved {
         }
     }
 
-    pub fn import_from_file(path: &Path) -> Option<Self> {
-        unimplemented!()
+    pub fn import_from_file(path: &Path) -> Result<Self, AlertFailure> {
+        let mut file = BufReader::new(File::open(path).map_err(|_| AlertFailure::FileIOError)?);
+        let board_saved = Self::read_from(&mut file)?;
+        let mut rest = [0u8; 1];
+        match file.read(&mut rest) {
+            Ok(0) => Ok(board_saved),
+            Ok(_) => Err(AlertFailure::FileFormatError),
+            Err(_) => Err(AlertFailure::FileIOError),
+        }
+    }
+
+    pub fn export_to_file(&self, path: &Path) -> Result<(), AlertFailure> {
+        let mut file = BufWriter::new(File::create(path).map_err(|_| AlertFailure::FileIOError)?);
+        self.write_to(&mut file)
+            .and_then(|_| file.flush())
+            .map_err(|_| AlertFailure::FileIOError)
+    }
+
+    pub fn read_from<R: Read>(r: &mut R) -> Result<Self, AlertFailure> {
+        let mut magic = [0u8; 4];
+        read_bytes(r, &mut magic)?;
+        if &magic != MAP_FILE_MAGIC {
+            return Err(AlertFailure::FileFormatError);
+        }
+        let version = read_u16(r)?;
+        if version != MAP_FILE_VERSION {
+            return Err(AlertFailure::FileVersionError(version));
+        }
+        let height = read_u32(r)? as usize;
+        let width = read_u32(r)? as usize;
+        let count = read_u32(r)? as usize;
+        let map_size = Self::checked_map_size((height, width))?;
+        if count >= map_size {
+            return Err(AlertFailure::InvalidMapData);
+        }
+        let mut mine_pos = Vec::with_capacity(count);
+        let mut mine_set = BTreeSet::new();
+        for _ in 0..count {
+            let mine_idx = read_u32(r)? as usize;
+            if mine_idx >= map_size || !mine_set.insert(mine_idx) {
+                return Err(AlertFailure::InvalidMapData);
+            }
+            mine_pos.push(mine_idx);
+        }
+        Ok(BoardSaved {
+            board_size: (height, width),
+            mine_pos: Rc::new(mine_pos),
+        })
+    }
+
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let (height, width) = self.board_size;
+        w.write_all(MAP_FILE_MAGIC)?;
+        w.write_all(&MAP_FILE_VERSION.to_le_bytes())?;
+        w.write_all(&(height as u32).to_le_bytes())?;
+        w.write_all(&(width as u32).to_le_bytes())?;
+        w.write_all(&(self.mine_pos.len() as u32).to_le_bytes())?;
+        for &mine_idx in self.mine_pos.iter() {
+            w.write_all(&(mine_idx as u32).to_le_bytes())?;
+        }
+        Ok(())
+    }
+
+    pub fn board_setting(&self) -> BoardSetting {
+        BoardSetting {
+            x: self.board_size.1,
+            y: self.board_size.0,
+            c: self.mine_pos.len(),
+            k: BoardSettingKind::Custom,
+        }
+    }
+
+    /// `board_seed` is only encoded when it still generates exactly this mine
+    /// field, otherwise the mines are written out as a bitmap.
+    pub fn export_to_code(&self, board_seed: Option<BoardSeed>) -> String {
+        let (height, width) = self.board_size;
+        let map_size = height * width;
+        let count = self.mine_pos.len();
+        let board_seed = board_seed.filter(|&board_seed| {
+            let mut generated = Self::generate_from_seed(self.board_size, count, board_seed);
+            let mut mine_pos = self.mine_pos.to_vec();
+            generated.sort_unstable();
+            mine_pos.sort_unstable();
+            generated == mine_pos
+        });
+
+        let mut payload = vec![BOARD_CODE_VERSION];
+        payload.push(if board_seed.is_some() {
+            BOARD_CODE_KIND_SEED
+        } else {
+            BOARD_CODE_KIND_BITMAP
+        });
+        payload.extend_from_slice(&(height as u16).to_le_bytes());
+        payload.extend_from_slice(&(width as u16).to_le_bytes());
+        payload.extend_from_slice(&(count as u32).to_le_bytes());
+        if let Some(board_seed) = board_seed {
+            let (y, x) = board_seed.first_click;
+            let mut flags = 0;
+            if board_seed.safe_start {
+                flags |= BOARD_CODE_FLAG_SAFE_START;
+            }
+            if board_seed.no_guess {
+                flags |= BOARD_CODE_FLAG_NO_GUESS;
+            }
+            payload.extend_from_slice(&board_seed.seed.to_le_bytes());
+            payload.extend_from_slice(&((y * width + x) as u32).to_le_bytes());
+            payload.push(flags);
+        } else {
+            let mut bitmap = vec![0u8; (map_size + 7) / 8];
+            for &idx in self.mine_pos.iter() {
+                bitmap[idx / 8] |= 1 << (idx % 8);
+            }
+            payload.extend_from_slice(&bitmap);
+        }
+        let checksum = fnv1a_hash(&payload);
+        payload.extend_from_slice(&checksum.to_le_bytes());
+        base32_encode(&payload)
+    }
+
+    pub fn import_from_code(code: &str) -> Result<Self, AlertFailure> {
+        let payload = base32_decode(code).ok_or(AlertFailure::InvalidBoardCode)?;
+        if payload.len() < 4 {
+            return Err(AlertFailure::InvalidBoardCode);
+        }
+        let (mut data, checksum) = payload.split_at(payload.len() - 4);
+        if fnv1a_hash(data).to_le_bytes() != checksum {
+            return Err(AlertFailure::InvalidBoardCode);
+        }
+        match Self::read_code_payload(&mut data) {
+            Ok(board_saved) if data.is_empty() => Ok(board_saved),
+            _ => Err(AlertFailure::InvalidBoardCode),
+        }
+    }
+
+    fn read_code_payload(data: &mut &[u8]) -> Result<Self, AlertFailure> {
+        let mut header = [0u8; 2];
+        read_bytes(data, &mut header)?;
+        if header[0] != BOARD_CODE_VERSION {
+            return Err(AlertFailure::InvalidBoardCode);
+        }
+        let height = read_u16(data)? as usize;
+        let width = read_u16(data)? as usize;
+        let count = read_u32(data)? as usize;
+        let map_size = Self::checked_map_size((height, width))?;
+        if count >= map_size {
+            return Err(AlertFailure::InvalidMapData);
+        }
+        let mine_pos = match header[1] {
+            BOARD_CODE_KIND_SEED => {
+                let mut seed = [0u8; 8];
+                read_bytes(data, &mut seed)?;
+                let first_click = read_u32(data)? as usize;
+                let mut flags = [0u8; 1];
+                read_bytes(data, &mut flags)?;
+                if first_click >= map_size
+                    || flags[0] & !(BOARD_CODE_FLAG_SAFE_START | BOARD_CODE_FLAG_NO_GUESS) != 0
+                {
+                    return Err(AlertFailure::InvalidBoardCode);
+                }
+                let board_seed = BoardSeed {
+                    seed: u64::from_le_bytes(seed),
+                    first_click: (first_click / width, first_click % width),
+                    safe_start: flags[0] & BOARD_CODE_FLAG_SAFE_START != 0,
+                    no_guess: flags[0] & BOARD_CODE_FLAG_NO_GUESS != 0,
+                };
+                Self::generate_from_seed((height, width), count, board_seed)
+            }
+            BOARD_CODE_KIND_BITMAP => {
+                let mut bitmap = vec![0u8; (map_size + 7) / 8];
+                read_bytes(data, &mut bitmap)?;
+                if bitmap
+                    .iter()
+                    .map(|b| b.count_ones() as usize)
+                    .sum::<usize>()
+                    != count
+                {
+                    return Err(AlertFailure::InvalidMapData);
+                }
+                (0..map_size)
+                    .filter(|&idx| bitmap[idx / 8] & (1 << (idx % 8)) != 0)
+                    .collect()
+            }
+            _ => return Err(AlertFailure::InvalidBoardCode),
+        };
+        Ok(BoardSaved {
+            board_size: (height, width),
+            mine_pos: Rc::new(mine_pos),
+        })
+    }
+
+    fn generate_from_seed(
+        (height, width): (usize, usize),
+        count: usize,
+        board_seed: BoardSeed,
+    ) -> Vec<usize> {
+        Board::new(height, width, count)
+            .generate_mine_pos_list(board_seed)
+            .0
+    }
+
+    fn checked_map_size((height, width): (usize, usize)) -> Result<usize, AlertFailure> {
+        if height == 0 || width == 0 || height > MAP_MAX_DIMENSION || width > MAP_MAX_DIMENSION {
+            return Err(AlertFailure::InvalidMapData);
+        }
+        Ok(height * width)
+    }
+}
+
+// `.cmm` layout, all little-endian: magic, u16 version, u32 height, u32 width,
+// u32 mine count, then one u32 row-major block index per mine.
+const MAP_FILE_MAGIC: &[u8; 4] = b"CMMF";
+const MAP_FILE_VERSION: u16 = 1;
+const MAP_MAX_DIMENSION: usize = 1024;
+
+fn read_bytes<R: Read>(r: &mut R, buf: &mut [u8]) -> Result<(), AlertFailure> {
+    r.read_exact(buf).map_err(|e| match e.kind() {
+        io::ErrorKind::UnexpectedEof => AlertFailure::FileFormatError,
+        _ => AlertFailure::FileIOError,
+    })
+}
+
+fn read_u16<R: Read>(r: &mut R) -> Result<u16, AlertFailure> {
+    let mut buf = [0u8; 2];
+    read_bytes(r, &mut buf)?;
+    Ok(u16::from_le_bytes(buf))
+}
+
+fn read_u32<R: Read>(r: &mut R) -> Result<u32, AlertFailure> {
+    let mut buf = [0u8; 4];
+    read_bytes(r, &mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+// Board codes are Crockford base32 of, little-endian: u8 version, u8 kind,
+// u16 height, u16 width, u32 mine count, then either the u64 generation seed,
+// u32 row-major index of the first click and u8 generation flags, or a
+// row-major mine bitmap (LSB first), and finally a u32 FNV-1a checksum of
+// everything before it.
+const BOARD_CODE_VERSION: u8 = 1;
+const BOARD_CODE_KIND_SEED: u8 = 0;
+const BOARD_CODE_KIND_BITMAP: u8 = 1;
+const BOARD_CODE_FLAG_SAFE_START: u8 = 0x01;
+const BOARD_CODE_FLAG_NO_GUESS: u8 = 0x02;
+const BASE32_ALPHABET: &[u8; 32] = b"0123456789ABCDEFGHJKMNPQRSTVWXYZ";
+
+fn fnv1a_hash(data: &[u8]) -> u32 {
+    let mut hash = 0x811C_9DC5_u32;
+    for &byte in data {
+        hash ^= byte as u32;
+        hash = hash.wrapping_mul(0x0100_0193);
     }
+    hash
+}
 
-    pub fn export_to_file(&self, path: &Path) -> Result<(), ()> {
-        unimplemented!()
+fn base32_encode(data: &[u8]) -> String {
+    let mut result = String::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for &byte in data {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+        while bits >= 5 {
+            bits -= 5;
+            result.push(BASE32_ALPHABET[((buffer >> bits) & 0x1F) as usize] as char);
+        }
+    }
+    if bits > 0 {
+        result.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1F) as usize] as char);
+    }
+    result
+}
+
+fn base32_decode(code: &str) -> Option<Vec<u8>> {
+    let mut result = Vec::new();
+    let mut buffer = 0u32;
+    let mut bits = 0;
+    for c in code.chars() {
+        let value = match c.to_ascii_uppercase() {
+            '-' => continue,
+            c if c.is_whitespace() => continue,
+            'O' => 0,
+            'I' | 'L' => 1,
+            c => BASE32_ALPHABET.iter().position(|&a| a as char == c)? as u32,
+        };
+        buffer = (buffer << 5) | value;
+        bits += 5;
+        if bits >= 8 {
+            bits -= 8;
+            result.push((buffer >> bits) as u8);
+        }
+    }
+    if buffer & ((1 << bits) - 1) != 0 {
+        return None;
+    }
+    Some(result)
+}
+
+#[derive(Clone, Debug)]
+pub struct RecordedCommand {
+    pub offset: Duration,
+    pub command: ModelCommand,
+}
+
+#[derive(Clone, Debug)]
+pub struct GameRecord {
+    pub board_saved: BoardSaved,
+    pub start_time: DateTime<Local>,
+    pub allow_marks: bool,
+    pub commands: Vec<RecordedCommand>,
+}
+
+impl GameRecord {
+    pub fn import_from_file(path: &Path) -> Result<Self, AlertFailure> {
+        let mut file = BufReader::new(File::open(path).map_err(|_| AlertFailure::FileIOError)?);
+        Self::read_from(&mut file)
+    }
+
+    pub fn read_from<R: Read>(file: &mut R) -> Result<Self, AlertFailure> {
+        let mut magic = [0u8; 4];
+        read_bytes(file, &mut magic)?;
+        if &magic != RECORD_FILE_MAGIC {
+            return Err(AlertFailure::FileFormatError);
+        }
+        let version = read_u16(file)?;
+        if version != RECORD_FILE_VERSION {
+            return Err(AlertFailure::FileVersionError(version));
+        }
+        let mut start_time = [0u8; 8];
+        read_bytes(file, &mut start_time)?;
+        let start_time = Local
+            .timestamp_millis_opt(i64::from_le_bytes(start_time))
+            .single()
+            .ok_or(AlertFailure::FileFormatError)?;
+        let mut flags = [0u8; 1];
+        read_bytes(file, &mut flags)?;
+        let allow_marks = flags[0] & RECORD_FLAG_ALLOW_MARKS != 0;
+        let board_saved = BoardSaved::read_from(file)?;
+
+        let mut commands = Vec::new();
+        let mut last_offset = Duration::from_millis(0);
+        loop {
+            let mut entry = [0u8; RECORD_ENTRY_SIZE];
+            // a record that was never finalized simply ends after its last entry.
+            match file.read(&mut entry[..1]) {
+                Ok(0) => break,
+                Ok(_) => {}
+                Err(_) => return Err(AlertFailure::FileIOError),
+            }
+            read_bytes(file, &mut entry[1..])?;
+            let mut field = [0u8; 4];
+            field.copy_from_slice(&entry[1..5]);
+            let offset = Duration::from_millis(u32::from_le_bytes(field) as u64);
+            field.copy_from_slice(&entry[5..9]);
+            let y = u32::from_le_bytes(field) as usize;
+            field.copy_from_slice(&entry[9..13]);
+            let x = u32::from_le_bytes(field) as usize;
+            if offset < last_offset {
+                return Err(AlertFailure::FileFormatError);
+            }
+            last_offset = offset;
+            if entry[0] == RECORD_TAG_FINISH {
+                break;
+            }
+            if y >= board_saved.board_size.0 || x >= board_saved.board_size.1 {
+                return Err(AlertFailure::InvalidMapData);
+            }
+            let command = match entry[0] {
+                RECORD_TAG_OPEN_BLOCK => ModelCommand::OpenBlock(y, x),
+                RECORD_TAG_BLAST_BLOCK => ModelCommand::BlastBlock(y, x),
+                RECORD_TAG_ROTATE_BLOCK_STATE => ModelCommand::RotateBlockState(y, x),
+                _ => return Err(AlertFailure::FileFormatError),
+            };
+            commands.push(RecordedCommand { offset, command });
+        }
+
+        Ok(GameRecord {
+            board_saved,
+            start_time,
+            allow_marks,
+            commands,
+        })
+    }
+
+    fn write_header<W: Write>(
+        w: &mut W,
+        board_saved: &BoardSaved,
+        start_time: DateTime<Local>,
+        allow_marks: bool,
+    ) -> io::Result<()> {
+        let flags = if allow_marks {
+            RECORD_FLAG_ALLOW_MARKS
+        } else {
+            0
+        };
+        let mut header = Vec::new();
+        header.extend_from_slice(RECORD_FILE_MAGIC);
+        header.extend_from_slice(&RECORD_FILE_VERSION.to_le_bytes());
+        header.extend_from_slice(&start_time.timestamp_millis().to_le_bytes());
+        header.push(flags);
+        board_saved.write_to(&mut header)?;
+        w.write_all(&header)?;
+        w.flush()
+    }
+
+    pub fn append_command<W: Write>(
+        w: &mut W,
+        offset: Duration,
+        command: &ModelCommand,
+    ) -> Result<(), AlertFailure> {
+        let (tag, y, x) = match *command {
+            ModelCommand::OpenBlock(y, x) => (RECORD_TAG_OPEN_BLOCK, y, x),
+            ModelCommand::BlastBlock(y, x) => (RECORD_TAG_BLAST_BLOCK, y, x),
+            ModelCommand::RotateBlockState(y, x) => (RECORD_TAG_ROTATE_BLOCK_STATE, y, x),
+            _ => return Ok(()),
+        };
+        Self::write_entry(w, tag, offset, (y, x))
+    }
+
+    pub fn append_finish<W: Write>(w: &mut W, offset: Duration) -> Result<(), AlertFailure> {
+        Self::write_entry(w, RECORD_TAG_FINISH, offset, (0, 0))
+    }
+
+    fn write_entry<W: Write>(
+        w: &mut W,
+        tag: u8,
+        offset: Duration,
+        (y, x): (usize, usize),
+    ) -> Result<(), AlertFailure> {
+        let offset_millis = Ord::min(offset.as_millis(), u32::MAX as u128) as u32;
+        let mut entry = [0u8; RECORD_ENTRY_SIZE];
+        entry[0] = tag;
+        entry[1..5].copy_from_slice(&offset_millis.to_le_bytes());
+        entry[5..9].copy_from_slice(&(y as u32).to_le_bytes());
+        entry[9..13].copy_from_slice(&(x as u32).to_le_bytes());
+        w.write_all(&entry)
+            .and_then(|_| w.flush())
+            .map_err(|_| AlertFailure::FileIOError)
+    }
+}
+
+/// A `.cmr` file being written. The header waits until the first move has
+/// placed the mines, so a recorded game starts out like any other game.
+#[derive(Debug)]
+pub struct GameRecorder {
+    file: File,
+    start_time: DateTime<Local>,
+    allow_marks: bool,
+    pending: Option<Vec<u8>>,
+}
+
+impl GameRecorder {
+    pub fn create_file(
+        path: &Path,
+        start_time: DateTime<Local>,
+        allow_marks: bool,
+    ) -> Result<Self, AlertFailure> {
+        let file = File::create(path).map_err(|_| AlertFailure::FileIOError)?;
+        Ok(GameRecorder {
+            file,
+            start_time,
+            allow_marks,
+            pending: Some(Vec::new()),
+        })
+    }
+
+    pub fn append_command(
+        &mut self,
+        board: &mut Board,
+        offset: Duration,
+        command: &ModelCommand,
+    ) -> Result<(), AlertFailure> {
+        match &mut self.pending {
+            Some(pending) => GameRecord::append_command(pending, offset, command)?,
+            None => GameRecord::append_command(&mut self.file, offset, command)?,
+        }
+        if board.status() != BoardStatus::Ready {
+            self.flush_pending(board)?;
+        }
+        Ok(())
+    }
+
+    pub fn append_finish(
+        &mut self,
+        board: &mut Board,
+        offset: Duration,
+    ) -> Result<(), AlertFailure> {
+        self.flush_pending(board)?;
+        GameRecord::append_finish(&mut self.file, offset)
+    }
+
+    fn flush_pending(&mut self, board: &mut Board) -> Result<(), AlertFailure> {
+        let pending = match self.pending.take() {
+            Some(pending) => pending,
+            None => return Ok(()),
+        };
+        let board_saved = BoardSaved::import_from_board(board);
+        GameRecord::write_header(
+            &mut self.file,
+            &board_saved,
+            self.start_time,
+            self.allow_marks,
+        )
+        .and_then(|_| self.file.write_all(&pending))
+        .and_then(|_| self.file.flush())
+        .map_err(|_| AlertFailure::FileIOError)
+    }
+}
+
+// `.cmr` layout, all little-endian: magic, u16 version, i64 start time in unix
+// milliseconds, u8 flags, an embedded `.cmm` map, then fixed-size entries of u8
+// tag, u32 milliseconds since start, u32 y and u32 x. A finish entry closes the
+// record.
+const RECORD_FILE_MAGIC: &[u8; 4] = b"CMRF";
+const RECORD_FILE_VERSION: u16 = 1;
+const RECORD_ENTRY_SIZE: usize = 13;
+
+const RECORD_FLAG_ALLOW_MARKS: u8 = 0x01;
+
+const RECORD_TAG_FINISH: u8 = 0;
+const RECORD_TAG_OPEN_BLOCK: u8 = 1;
+const RECORD_TAG_BLAST_BLOCK: u8 = 2;
+const RECORD_TAG_ROTATE_BLOCK_STATE: u8 = 3;
+
+#[derive(Debug)]
+pub struct Playback {
+    commands: Vec<RecordedCommand>,
+    next_idx: usize,
+    paused: bool,
+    speed_exp: i32,
+    anchor_instant: Instant,
+    anchor_offset: Duration,
+}
+
+impl Playback {
+    // playback speed is `2 ^ speed_exp`, from 0.25x to 8x.
+    const SPEED_EXP_MIN: i32 = -2;
+    const SPEED_EXP_MAX: i32 = 3;
+
+    pub fn new(commands: Vec<RecordedCommand>) -> Self {
+        Playback {
+            commands,
+            next_idx: 0,
+            paused: false,
+            speed_exp: 0,
+            anchor_instant: Instant::now(),
+            anchor_offset: Duration::from_millis(0),
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.commands.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.commands.is_empty()
+    }
+
+    pub fn position(&self) -> usize {
+        self.next_idx
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.next_idx >= self.commands.len()
+    }
+
+    pub fn speed(&self) -> f64 {
+        2f64.powi(self.speed_exp)
+    }
+
+    fn elapsed(&self) -> Duration {
+        if self.paused {
+            self.anchor_offset
+        } else {
+            self.anchor_offset + self.anchor_instant.elapsed().mul_f64(self.speed())
+        }
+    }
+
+    fn reanchor(&mut self, offset: Duration) {
+        self.anchor_offset = offset;
+        self.anchor_instant = Instant::now();
+    }
+
+    pub fn set_paused(&mut self, paused: bool) {
+        let elapsed = self.elapsed();
+        self.paused = paused;
+        self.reanchor(elapsed);
+    }
+
+    pub fn change_speed(&mut self, delta: i32) {
+        let elapsed = self.elapsed();
+        self.speed_exp = Ord::clamp(
+            self.speed_exp + delta,
+            Self::SPEED_EXP_MIN,
+            Self::SPEED_EXP_MAX,
+        );
+        self.reanchor(elapsed);
+    }
+
+    /// Moves the cursor so that `idx` commands have been played, returning them.
+    pub fn seek(&mut self, idx: usize) -> &[RecordedCommand] {
+        let idx = Ord::min(idx, self.commands.len());
+        let offset = match idx {
+            0 => Duration::from_millis(0),
+            _ => self.commands[idx - 1].offset,
+        };
+        self.next_idx = idx;
+        self.reanchor(offset);
+        &self.commands[..idx]
+    }
+
+    pub fn pop_due_command(&mut self) -> Option<ModelCommand> {
+        let recorded = self.commands.get(self.next_idx)?;
+        if recorded.offset > self.elapsed() {
+            return None;
+        }
+        self.next_idx += 1;
+        Some(recorded.command.clone())
     }
 }
 
 #[derive(Clone, Debug)]
 pub enum GameMode {
     Normal,
-    GameRecording(DateTime<Local>, Rc<RefCell<File>>),
+    GameRecording(DateTime<Local>, Rc<RefCell<GameRecorder>>),
     BoardPredefined(BoardSaved),
-    GamePlayback(BoardSaved, DateTime<Local>, Vec<ModelCommand>),
+    GamePlayback(BoardSaved, DateTime<Local>, Rc<RefCell<Playback>>),
 }
 
 impl GameMode {
@@ -89,3 +709,158 @@ impl GameMode {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_random;
+    use crate::model_storage::ScratchDir;
+
+    fn board_saved(seed: u64) -> BoardSaved {
+        BoardSaved {
+            board_size: (15, 21),
+            mine_pos: Rc::new(model_random::pick_mine_pos_list(seed, 15 * 21, 50, &[])),
+        }
+    }
+
+    fn sorted(mine_pos: &[usize]) -> Vec<usize> {
+        let mut mine_pos = mine_pos.to_vec();
+        mine_pos.sort_unstable();
+        mine_pos
+    }
+
+    /// Board opened at `first_click` the way a game starts, with its saved
+    /// layout.
+    fn played_board(
+        seed: u64,
+        first_click: (usize, usize),
+        safe_start: bool,
+        no_guess: bool,
+    ) -> (Board, BoardSaved) {
+        let mut board = Board::new(15, 21, 40);
+        board.set_seed(seed);
+        board.set_safe_start(safe_start);
+        board.set_no_guess(no_guess);
+        board.apply_command(&ModelCommand::OpenBlock(first_click.0, first_click.1));
+        let saved = BoardSaved::import_from_board(&mut board);
+        (board, saved)
+    }
+
+    #[test]
+    fn board_code_roundtrip() {
+        for &(safe_start, no_guess) in &[(false, false), (true, false), (false, true)] {
+            let (board, saved) = played_board(1234, (7, 10), safe_start, no_guess);
+            let seed_code = saved.export_to_code(board.board_seed());
+            let bitmap_code = saved.export_to_code(None);
+            assert!(seed_code.len() < bitmap_code.len());
+            for code in &[seed_code, bitmap_code] {
+                let loaded = BoardSaved::import_from_code(code).unwrap();
+                assert_eq!(loaded.board_size, saved.board_size);
+                assert_eq!(sorted(&loaded.mine_pos), sorted(&saved.mine_pos));
+            }
+        }
+    }
+
+    #[test]
+    fn board_code_ignores_unrelated_seed() {
+        let (board, saved) = played_board(1234, (0, 0), true, false);
+        let mut board_seed = board.board_seed().unwrap();
+        board_seed.seed = 1;
+        assert_eq!(
+            saved.export_to_code(Some(board_seed)),
+            saved.export_to_code(None)
+        );
+        let saved = board_saved(1234);
+        assert_eq!(
+            saved.export_to_code(board.board_seed()),
+            saved.export_to_code(None)
+        );
+    }
+
+    #[test]
+    fn board_code_is_forgiving_about_typing() {
+        let (board, saved) = played_board(99, (3, 4), false, false);
+        let code = saved.export_to_code(board.board_seed());
+        let (a, b) = code.split_at(code.len() / 2);
+        let typed = format!("{}-{}", a, b).to_ascii_lowercase();
+        assert!(BoardSaved::import_from_code(&typed).is_ok());
+    }
+
+    #[test]
+    fn corrupted_board_code_is_rejected() {
+        let code = board_saved(5).export_to_code(None);
+        let mut chars = code.chars().collect::<Vec<_>>();
+        chars[3] = if chars[3] == 'A' { 'B' } else { 'A' };
+        let corrupted = chars.into_iter().collect::<String>();
+        assert!(BoardSaved::import_from_code(&corrupted).is_err());
+        assert!(BoardSaved::import_from_code(&code[..code.len() - 2]).is_err());
+        assert!(BoardSaved::import_from_code("not a code").is_err());
+    }
+
+    #[test]
+    fn map_file_roundtrip() {
+        let saved = board_saved(77);
+        let mut data = Vec::new();
+        saved.write_to(&mut data).unwrap();
+        let loaded = BoardSaved::read_from(&mut &data[..]).unwrap();
+        assert_eq!(loaded.board_size, saved.board_size);
+        assert_eq!(*loaded.mine_pos, *saved.mine_pos);
+        assert!(BoardSaved::read_from(&mut &data[..data.len() - 1]).is_err());
+    }
+
+    #[test]
+    fn recorded_first_click_is_never_a_mine() {
+        let dir = ScratchDir::new("first-click");
+        let path = dir.path().join("game.cmr");
+        for seed in 0..20 {
+            let mut board = Board::new(10, 11, 10);
+            board.set_seed(seed);
+            let mut recorder = GameRecorder::create_file(&path, Local::now(), false).unwrap();
+            let command = ModelCommand::OpenBlock(4, 5);
+            board.apply_command(&command);
+            recorder
+                .append_command(&mut board, Duration::from_millis(0), &command)
+                .unwrap();
+            recorder
+                .append_finish(&mut board, Duration::from_millis(10))
+                .unwrap();
+            drop(recorder);
+            let record = GameRecord::import_from_file(&path).unwrap();
+            assert!(!record.board_saved.mine_pos.contains(&(4 * 11 + 5)));
+            assert_eq!(record.commands.len(), 1);
+        }
+    }
+
+    #[test]
+    fn record_file_roundtrip() {
+        let saved = board_saved(31);
+        let start_time = Local
+            .timestamp_millis_opt(1_600_000_000_000)
+            .single()
+            .unwrap();
+        let commands = [
+            ModelCommand::OpenBlock(3, 4),
+            ModelCommand::RotateBlockState(0, 20),
+            ModelCommand::BlastBlock(14, 0),
+        ];
+        let mut data = Vec::new();
+        GameRecord::write_header(&mut data, &saved, start_time, false).unwrap();
+        for (i, command) in commands.iter().enumerate() {
+            let offset = Duration::from_millis(100 * i as u64);
+            GameRecord::append_command(&mut data, offset, command).unwrap();
+        }
+        GameRecord::append_finish(&mut data, Duration::from_millis(500)).unwrap();
+
+        let record = GameRecord::read_from(&mut &data[..]).unwrap();
+        assert_eq!(record.start_time, start_time);
+        assert!(!record.allow_marks);
+        assert_eq!(record.board_saved.board_size, saved.board_size);
+        assert_eq!(*record.board_saved.mine_pos, *saved.mine_pos);
+        assert_eq!(record.commands.len(), 3);
+        assert_eq!(record.commands[2].offset, Duration::from_millis(200));
+        assert!(matches!(
+            record.commands[1].command,
+            ModelCommand::RotateBlockState(0, 20)
+        ));
+    }
+}
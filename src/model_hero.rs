@@ -0,0 +1,159 @@
+use crate::model_config::BoardSettingKind;
+use crate::model_storage::{KeyValueFile, Storage};
+use crate::view_command::AlertFailure;
+use std::path::Path;
+use std::time::Duration;
+
+#[derive(Clone, Debug)]
+pub struct HeroRecord {
+    pub name: String,
+    pub time: Duration,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HeroList {
+    records: [Option<HeroRecord>; 3],
+}
+
+impl HeroList {
+    const FILE_NAME: &'static str = "heroes.ini";
+    const KEYS: [&'static str; 3] = ["easy", "normal", "hard"];
+
+    pub const DEFAULT_NAME: &'static str = "Anonymous";
+
+    fn slot(kind: BoardSettingKind) -> Option<usize> {
+        match kind {
+            BoardSettingKind::Easy => Some(0),
+            BoardSettingKind::Normal => Some(1),
+            BoardSettingKind::Hard => Some(2),
+            BoardSettingKind::Custom => None,
+        }
+    }
+
+    pub fn load(storage: &Storage) -> Self {
+        match storage.data_file(Self::FILE_NAME) {
+            Some(path) => Self::load_from(&path),
+            None => HeroList::default(),
+        }
+    }
+
+    pub fn load_from(path: &Path) -> Self {
+        let mut hero_list = HeroList::default();
+        let file = KeyValueFile::load(path);
+        for (record, key) in hero_list.records.iter_mut().zip(Self::KEYS.iter()) {
+            let name = file.get(&format!("{}.name", key));
+            let time_ms = file.get_parsed::<u64>(&format!("{}.time_ms", key));
+            if let (Some(name), Some(time_ms)) = (name, time_ms) {
+                *record = Some(HeroRecord {
+                    name: name.to_owned(),
+                    time: Duration::from_millis(time_ms),
+                });
+            }
+        }
+        hero_list
+    }
+
+    pub fn save(&self, storage: &Storage) -> Result<(), AlertFailure> {
+        let path = storage
+            .data_file(Self::FILE_NAME)
+            .ok_or(AlertFailure::FileIOError)?;
+        self.save_to(&path)
+    }
+
+    pub fn save_to(&self, path: &Path) -> Result<(), AlertFailure> {
+        // entries written by other versions are kept as they are.
+        let mut file = KeyValueFile::load(path);
+        for (record, key) in self.records.iter().zip(Self::KEYS.iter()) {
+            let name_key = format!("{}.name", key);
+            let time_key = format!("{}.time_ms", key);
+            if let Some(record) = record {
+                file.set(&name_key, &record.name);
+                file.set(&time_key, record.time.as_millis());
+            } else {
+                file.remove(&name_key);
+                file.remove(&time_key);
+            }
+        }
+        file.save(path).map_err(|_| AlertFailure::FileIOError)
+    }
+
+    pub fn record(&self, kind: BoardSettingKind) -> Option<&HeroRecord> {
+        Self::slot(kind).and_then(|slot| self.records[slot].as_ref())
+    }
+
+    pub fn is_new_record(&self, kind: BoardSettingKind, time: Duration) -> bool {
+        match Self::slot(kind) {
+            Some(slot) => match &self.records[slot] {
+                Some(record) => time < record.time,
+                None => true,
+            },
+            None => false,
+        }
+    }
+
+    pub fn update(&mut self, kind: BoardSettingKind, record: HeroRecord) {
+        if let Some(slot) = Self::slot(kind) {
+            self.records[slot] = Some(record);
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.records = Default::default();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_storage::ScratchDir;
+    use std::fs;
+
+    fn hero(name: &str, time_ms: u64) -> HeroRecord {
+        HeroRecord {
+            name: name.to_owned(),
+            time: Duration::from_millis(time_ms),
+        }
+    }
+
+    #[test]
+    fn only_faster_times_are_records() {
+        let mut hero_list = HeroList::default();
+        assert!(hero_list.is_new_record(BoardSettingKind::Easy, Duration::from_secs(999)));
+        assert!(!hero_list.is_new_record(BoardSettingKind::Custom, Duration::from_secs(1)));
+
+        hero_list.update(BoardSettingKind::Easy, hero("a", 20_000));
+        assert!(hero_list.is_new_record(BoardSettingKind::Easy, Duration::from_secs(19)));
+        assert!(!hero_list.is_new_record(BoardSettingKind::Easy, Duration::from_secs(20)));
+        assert!(!hero_list.is_new_record(BoardSettingKind::Easy, Duration::from_secs(21)));
+        assert!(hero_list.is_new_record(BoardSettingKind::Hard, Duration::from_secs(999)));
+
+        hero_list.update(BoardSettingKind::Easy, hero("b", 15_000));
+        hero_list.update(BoardSettingKind::Custom, hero("c", 1_000));
+        assert_eq!(hero_list.record(BoardSettingKind::Easy).unwrap().name, "b");
+        assert!(hero_list.record(BoardSettingKind::Normal).is_none());
+        assert!(hero_list.record(BoardSettingKind::Custom).is_none());
+    }
+
+    #[test]
+    fn hero_file_roundtrip() {
+        let dir = ScratchDir::new("hero");
+        let path = dir.path().join(HeroList::FILE_NAME);
+        fs::write(&path, "easy.name=old\neasy.time_ms=1\nfuture.key=kept\n").unwrap();
+
+        let mut hero_list = HeroList::load_from(&path);
+        assert_eq!(
+            hero_list.record(BoardSettingKind::Easy).unwrap().name,
+            "old"
+        );
+        hero_list.reset();
+        hero_list.update(BoardSettingKind::Normal, hero("Charles", 61_250));
+        hero_list.save_to(&path).unwrap();
+
+        let loaded = HeroList::load_from(&path);
+        assert!(loaded.record(BoardSettingKind::Easy).is_none());
+        let record = loaded.record(BoardSettingKind::Normal).unwrap();
+        assert_eq!(record.name, "Charles");
+        assert_eq!(record.time, Duration::from_millis(61_250));
+        assert_eq!(KeyValueFile::load(&path).get("future.key"), Some("kept"));
+    }
+}
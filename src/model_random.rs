@@ -0,0 +1,95 @@
+/// SplitMix64, as published by Sebastiano Vigna. Mine layouts are derived
+/// from this exact sequence, so the algorithm and its constants must never
+/// change, or previously shared seeds will produce different boards.
+#[derive(Clone, Debug)]
+pub struct SeedRng {
+    state: u64,
+}
+
+impl SeedRng {
+    pub fn new(seed: u64) -> Self {
+        SeedRng { state: seed }
+    }
+
+    pub fn next_u64(&mut self) -> u64 {
+        self.state = self.state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+
+    /// Uniform value in `0..bound`, using rejection sampling to avoid
+    /// modulo bias.
+    pub fn next_below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0);
+        let limit = u64::MAX - u64::MAX % bound;
+        loop {
+            let v = self.next_u64();
+            if v < limit {
+                return v % bound;
+            }
+        }
+    }
+}
+
+/// Picks `count` distinct cells out of `0..map_size`, never choosing one in
+/// `exclude`. The candidates are listed in ascending order and shuffled
+/// with a partial Fisher-Yates pass driven by `SeedRng`.
+pub fn pick_mine_pos_list(
+    seed: u64,
+    map_size: usize,
+    count: usize,
+    exclude: &[usize],
+) -> Vec<usize> {
+    let mut candidates = (0..map_size)
+        .filter(|idx| !exclude.contains(idx))
+        .collect::<Vec<_>>();
+    let count = count.min(candidates.len());
+    let mut rng = SeedRng::new(seed);
+    for i in 0..count {
+        let j = i + rng.next_below((candidates.len() - i) as u64) as usize;
+        candidates.swap(i, j);
+    }
+    candidates.truncate(count);
+    candidates
+}
+
+pub fn random_seed() -> u64 {
+    use rand::Rng;
+    rand::thread_rng().gen()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_seed_picks_same_mines() {
+        let a = pick_mine_pos_list(42, 110, 10, &[]);
+        let b = pick_mine_pos_list(42, 110, 10, &[]);
+        assert_eq!(a, b);
+        assert_ne!(a, pick_mine_pos_list(43, 110, 10, &[]));
+    }
+
+    #[test]
+    fn picked_mines_are_distinct_and_not_excluded() {
+        let exclude = [0, 1, 2, 11, 12, 13];
+        for seed in 0..100 {
+            let mut list = pick_mine_pos_list(seed, 110, 50, &exclude);
+            assert_eq!(list.len(), 50);
+            assert!(list.iter().all(|idx| *idx < 110 && !exclude.contains(idx)));
+            list.sort_unstable();
+            list.dedup();
+            assert_eq!(list.len(), 50);
+        }
+    }
+
+    #[test]
+    fn next_below_stays_in_range() {
+        let mut rng = SeedRng::new(7);
+        for bound in 1..200 {
+            assert!(rng.next_below(bound) < bound);
+        }
+    }
+}
@@ -0,0 +1,684 @@
+use crate::model::{BlockStatus, Board};
+use smallvec::SmallVec;
+use std::collections::BTreeMap;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub enum SolverCell {
+    Unknown,
+    Mine,
+    Open(u8),
+}
+
+pub struct SolverGrid {
+    size: (usize, usize),
+    cells: Vec<SolverCell>,
+    neighbors: Vec<SmallVec<[usize; 12]>>,
+    mine_count: Option<usize>,
+}
+
+impl SolverGrid {
+    pub fn new(size: (usize, usize)) -> Self {
+        let (height, width) = size;
+        let mut neighbors = Vec::with_capacity(height * width);
+        for y in 0..height {
+            for x in 0..width {
+                let list = Board::surrounding_blocks(y as isize, x as isize)
+                    .iter()
+                    .filter(|&&(ny, nx)| {
+                        ny >= 0 && ny < height as isize && nx >= 0 && nx < width as isize
+                    })
+                    .map(|&(ny, nx)| ny as usize * width + nx as usize)
+                    .collect();
+                neighbors.push(list);
+            }
+        }
+        SolverGrid {
+            size,
+            cells: vec![SolverCell::Unknown; height * width],
+            neighbors,
+            mine_count: None,
+        }
+    }
+
+    /// Builds the grid from what the player can see. Flagged blocks are
+    /// trusted to be mines.
+    pub fn from_board(board: &Board) -> Self {
+        Self::from_board_with(board, true)
+    }
+
+    /// Like `from_board`, but flagged blocks count as unknown, so a wrong flag
+    /// cannot lead to a wrong deduction.
+    pub fn from_board_without_flags(board: &Board) -> Self {
+        Self::from_board_with(board, false)
+    }
+
+    fn from_board_with(board: &Board, trust_flags: bool) -> Self {
+        let (height, width) = board.size();
+        let mut grid = SolverGrid::new((height, width));
+        for y in 0..height {
+            for x in 0..width {
+                grid.cells[y * width + x] = match board.block_status(y, x) {
+                    BlockStatus::Open => match board.block_display_number(y, x) {
+                        Some(number) => SolverCell::Open(number),
+                        None => SolverCell::Mine,
+                    },
+                    BlockStatus::MarkedMine if trust_flags => SolverCell::Mine,
+                    BlockStatus::MarkedMine
+                    | BlockStatus::Normal
+                    | BlockStatus::MarkedQuestionable => SolverCell::Unknown,
+                };
+            }
+        }
+        grid.mine_count = Some(board.goal_mark_count());
+        grid
+    }
+
+    pub fn size(&self) -> (usize, usize) {
+        self.size
+    }
+
+    pub fn cell(&self, idx: usize) -> SolverCell {
+        self.cells[idx]
+    }
+
+    pub fn set_cell(&mut self, idx: usize, cell: SolverCell) {
+        self.cells[idx] = cell;
+    }
+
+    pub fn neighbors(&self, idx: usize) -> &[usize] {
+        &self.neighbors[idx]
+    }
+
+    pub fn cells(&self) -> &[SolverCell] {
+        &self.cells
+    }
+
+    pub fn position(&self, idx: usize) -> (usize, usize) {
+        (idx / self.size.1, idx % self.size.1)
+    }
+
+    pub fn mine_count(&self) -> Option<usize> {
+        self.mine_count
+    }
+
+    pub fn set_mine_count(&mut self, mine_count: Option<usize>) {
+        self.mine_count = mine_count;
+    }
+
+    /// One constraint per open number that still touches unknown blocks: the
+    /// listed blocks hold exactly `mines` mines.
+    fn constraints(&self) -> Vec<Constraint> {
+        let mut constraints = Vec::new();
+        for (idx, cell) in self.cells.iter().enumerate() {
+            let number = match *cell {
+                SolverCell::Open(number) => number as usize,
+                _ => continue,
+            };
+            let mut known_mines = 0;
+            let mut cells = SmallVec::new();
+            for &n in self.neighbors(idx) {
+                match self.cells[n] {
+                    SolverCell::Mine => known_mines += 1,
+                    SolverCell::Unknown => cells.push(n),
+                    SolverCell::Open(_) => {}
+                }
+            }
+            if cells.is_empty() || known_mines > number {
+                continue;
+            }
+            cells.sort_unstable();
+            constraints.push(Constraint {
+                cells,
+                mines: number - known_mines,
+            });
+        }
+        constraints
+    }
+}
+
+struct Constraint {
+    cells: SmallVec<[usize; 12]>,
+    mines: usize,
+}
+
+impl Constraint {
+    fn is_subset_of(&self, other: &Constraint) -> bool {
+        self.cells
+            .iter()
+            .all(|c| other.cells.binary_search(c).is_ok())
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Deductions {
+    pub safe: Vec<usize>,
+    pub mines: Vec<usize>,
+}
+
+impl Deductions {
+    pub fn is_empty(&self) -> bool {
+        self.safe.is_empty() && self.mines.is_empty()
+    }
+}
+
+fn mark_cells(cells: &[usize], mines: usize, safe_list: &mut [bool], mine_list: &mut [bool]) {
+    if mines == 0 {
+        for &c in cells {
+            safe_list[c] = true;
+        }
+    } else if mines == cells.len() {
+        for &c in cells {
+            mine_list[c] = true;
+        }
+    }
+}
+
+/// Finds blocks that are certainly safe or certainly mined, using
+/// - each open number on its own,
+/// - pairs of numbers where one's unknown blocks are a subset of the other's,
+/// - the total mine count, when the grid knows it.
+pub fn deduce(grid: &SolverGrid) -> Deductions {
+    let cell_count = grid.cells.len();
+    let mut safe = vec![false; cell_count];
+    let mut mines = vec![false; cell_count];
+    let constraints = grid.constraints();
+
+    for constraint in constraints.iter() {
+        mark_cells(&constraint.cells, constraint.mines, &mut safe, &mut mines);
+    }
+
+    let mut cell_constraints = vec![SmallVec::<[usize; 12]>::new(); cell_count];
+    for (constraint_idx, constraint) in constraints.iter().enumerate() {
+        for &c in constraint.cells.iter() {
+            cell_constraints[c].push(constraint_idx);
+        }
+    }
+    for subset in constraints.iter() {
+        // any superset has to contain the first block of the subset too.
+        for &superset_idx in cell_constraints[subset.cells[0]].iter() {
+            let superset = &constraints[superset_idx];
+            if superset.cells.len() <= subset.cells.len()
+                || superset.mines < subset.mines
+                || !subset.is_subset_of(superset)
+            {
+                continue;
+            }
+            let rest = superset
+                .cells
+                .iter()
+                .copied()
+                .filter(|c| subset.cells.binary_search(c).is_err())
+                .collect::<SmallVec<[usize; 12]>>();
+            mark_cells(&rest, superset.mines - subset.mines, &mut safe, &mut mines);
+        }
+    }
+
+    if let Some(mine_count) = grid.mine_count {
+        let known_mines = grid
+            .cells
+            .iter()
+            .filter(|&&c| c == SolverCell::Mine)
+            .count();
+        if mine_count >= known_mines {
+            let rest_mines = mine_count - known_mines;
+            let unknown = (0..cell_count)
+                .filter(|&idx| grid.cells[idx] == SolverCell::Unknown)
+                .collect::<Vec<_>>();
+            mark_cells(&unknown, rest_mines, &mut safe, &mut mines);
+
+            // numbers that share no blocks need at least the sum of their
+            // mines; if that is all that is left, every other block is safe.
+            let mut covered = vec![false; cell_count];
+            let mut covered_mines = 0;
+            for constraint in constraints.iter() {
+                if constraint.cells.iter().any(|&c| covered[c]) {
+                    continue;
+                }
+                for &c in constraint.cells.iter() {
+                    covered[c] = true;
+                }
+                covered_mines += constraint.mines;
+            }
+            if covered_mines == rest_mines {
+                for &idx in unknown.iter() {
+                    if !covered[idx] {
+                        safe[idx] = true;
+                    }
+                }
+            }
+        }
+    }
+
+    Deductions {
+        safe: (0..cell_count).filter(|&idx| safe[idx]).collect(),
+        mines: (0..cell_count).filter(|&idx| mines[idx]).collect(),
+    }
+}
+
+pub fn analyze_board(board: &Board) -> Deductions {
+    deduce(&SolverGrid::from_board(board))
+}
+
+const PROBABILITY_STEP_BUDGET: usize = 2_000_000;
+const PROBABILITY_COMPONENT_MAX: usize = 512;
+
+/// Exact mine probability of every block, weighing each consistent layout of
+/// the frontier by the number of ways to place the remaining mines in the
+/// unconstrained interior. Open blocks get 0 and known mines 1. Returns
+/// `None` without a total mine count, for contradictory states, or when the
+/// frontier is too large to enumerate.
+pub fn mine_probabilities(grid: &SolverGrid) -> Option<Vec<f64>> {
+    let cell_count = grid.cells.len();
+    let known_mines = grid
+        .cells
+        .iter()
+        .filter(|&&c| c == SolverCell::Mine)
+        .count();
+    let rest_mines = grid.mine_count?.checked_sub(known_mines)?;
+    let constraints = grid.constraints();
+
+    // blocks sharing a number belong to the same independent component.
+    let mut parent = (0..constraints.len()).collect::<Vec<_>>();
+    fn find(parent: &mut [usize], mut idx: usize) -> usize {
+        while parent[idx] != idx {
+            parent[idx] = parent[parent[idx]];
+            idx = parent[idx];
+        }
+        idx
+    }
+    let mut owner = vec![None; cell_count];
+    for (constraint_idx, constraint) in constraints.iter().enumerate() {
+        for &c in constraint.cells.iter() {
+            match owner[c] {
+                Some(other) => {
+                    let a = find(&mut parent, constraint_idx);
+                    let b = find(&mut parent, other);
+                    parent[a] = b;
+                }
+                None => owner[c] = Some(constraint_idx),
+            }
+        }
+    }
+    let mut groups = BTreeMap::<usize, Vec<usize>>::new();
+    for constraint_idx in 0..constraints.len() {
+        let root = find(&mut parent, constraint_idx);
+        groups.entry(root).or_default().push(constraint_idx);
+    }
+
+    let mut steps = 0;
+    let mut components = Vec::new();
+    for constraint_ids in groups.values() {
+        let mut cells = Vec::new();
+        for &constraint_idx in constraint_ids.iter() {
+            for &c in constraints[constraint_idx].cells.iter() {
+                if !cells.contains(&c) {
+                    cells.push(c);
+                }
+            }
+        }
+        if cells.len() > PROBABILITY_COMPONENT_MAX {
+            return None;
+        }
+        let mut enumeration = Enumeration::new(&constraints, constraint_ids, cells, rest_mines);
+        if !enumeration.search(0, 0, &mut steps) {
+            return None;
+        }
+        enumeration.normalize();
+        components.push(enumeration);
+    }
+
+    let interior = (0..cell_count)
+        .filter(|&idx| grid.cells[idx] == SolverCell::Unknown && owner[idx].is_none())
+        .count();
+    let frontier_mines_max = components.iter().map(|c| c.cells.len()).sum::<usize>();
+    let interior_weights = interior_weights(interior, rest_mines, frontier_mines_max);
+
+    // mine count distributions of all components but one, built from
+    // prefix and suffix products.
+    let mut prefix = vec![vec![1.0]];
+    for component in components.iter() {
+        let next = convolve(prefix.last().unwrap(), &component.weights);
+        prefix.push(next);
+    }
+    let mut suffix = vec![vec![1.0]];
+    for component in components.iter().rev() {
+        let next = convolve(suffix.last().unwrap(), &component.weights);
+        suffix.push(next);
+    }
+    suffix.reverse();
+
+    let total = prefix.last().unwrap();
+    let weight_of = |mines: usize| interior_weights.get(mines).copied().unwrap_or(0.0);
+    let norm = (0..total.len())
+        .map(|t| total[t] * weight_of(t))
+        .sum::<f64>();
+    if norm.is_nan() || norm <= 0.0 {
+        return None;
+    }
+
+    let mut result = grid
+        .cells
+        .iter()
+        .map(|&c| if c == SolverCell::Mine { 1.0 } else { 0.0 })
+        .collect::<Vec<_>>();
+    for (component_idx, component) in components.iter().enumerate() {
+        let others = convolve(&prefix[component_idx], &suffix[component_idx + 1]);
+        for (mines, cell_weights) in component.cell_weights.iter().enumerate() {
+            let factor = (0..others.len())
+                .map(|t| others[t] * weight_of(mines + t))
+                .sum::<f64>();
+            if factor == 0.0 {
+                continue;
+            }
+            for (local_idx, &c) in component.cells.iter().enumerate() {
+                result[c] += cell_weights[local_idx] * factor / norm;
+            }
+        }
+    }
+    if interior > 0 {
+        let interior_mines = (0..total.len())
+            .filter(|&t| t <= rest_mines)
+            .map(|t| total[t] * weight_of(t) * (rest_mines - t) as f64)
+            .sum::<f64>();
+        let probability = interior_mines / norm / interior as f64;
+        for idx in 0..cell_count {
+            if grid.cells[idx] == SolverCell::Unknown && owner[idx].is_none() {
+                result[idx] = probability;
+            }
+        }
+    }
+    Some(result)
+}
+
+/// Relative number of ways to put the remaining mines into the interior when
+/// the frontier holds `t` of them, indexed by `t` and scaled so that the
+/// largest is 1.
+fn interior_weights(interior: usize, rest_mines: usize, frontier_mines_max: usize) -> Vec<f64> {
+    fn ln_choose(n: usize, k: usize) -> f64 {
+        let k = k.min(n - k);
+        (0..k)
+            .map(|i| ((n - i) as f64).ln() - ((i + 1) as f64).ln())
+            .sum()
+    }
+    let t_max = frontier_mines_max.min(rest_mines);
+    let mut weights = vec![None; t_max + 1];
+    let mut ln_weight = None;
+    for t in (0..=t_max).rev() {
+        let interior_mines = rest_mines - t;
+        if interior_mines > interior {
+            break;
+        }
+        // C(n, k + 1) = C(n, k) * (n - k) / (k + 1), stepping k up as t goes down.
+        let ln = match ln_weight {
+            Some(prev) => {
+                let k = interior_mines - 1;
+                prev + ((interior - k) as f64).ln() - ((k + 1) as f64).ln()
+            }
+            None => ln_choose(interior, interior_mines),
+        };
+        ln_weight = Some(ln);
+        weights[t] = Some(ln);
+    }
+    let max = weights
+        .iter()
+        .filter_map(|&w| w)
+        .fold(f64::NEG_INFINITY, f64::max);
+    weights
+        .into_iter()
+        .map(|w| w.map_or(0.0, |w| (w - max).exp()))
+        .collect()
+}
+
+fn convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
+    let mut result = vec![0.0; a.len() + b.len() - 1];
+    for (i, &x) in a.iter().enumerate() {
+        if x == 0.0 {
+            continue;
+        }
+        for (j, &y) in b.iter().enumerate() {
+            result[i + j] += x * y;
+        }
+    }
+    result
+}
+
+/// Counts the layouts of one frontier component by backtracking, per total
+/// number of mines in it.
+struct Enumeration {
+    cells: Vec<usize>,
+    cell_constraints: Vec<SmallVec<[usize; 12]>>,
+    needed: Vec<usize>,
+    placed: Vec<usize>,
+    unassigned: Vec<usize>,
+    assignment: Vec<bool>,
+    mine_limit: usize,
+    weights: Vec<f64>,
+    cell_weights: Vec<Vec<f64>>,
+}
+
+impl Enumeration {
+    fn new(
+        constraints: &[Constraint],
+        constraint_ids: &[usize],
+        cells: Vec<usize>,
+        mine_limit: usize,
+    ) -> Self {
+        let mut cell_constraints = vec![SmallVec::new(); cells.len()];
+        let mut needed = Vec::new();
+        let mut unassigned = Vec::new();
+        for (local_idx, &constraint_idx) in constraint_ids.iter().enumerate() {
+            let constraint = &constraints[constraint_idx];
+            for &c in constraint.cells.iter() {
+                let cell_idx = cells.iter().position(|&x| x == c).unwrap();
+                cell_constraints[cell_idx].push(local_idx);
+            }
+            needed.push(constraint.mines);
+            unassigned.push(constraint.cells.len());
+        }
+        let cell_count = cells.len();
+        Enumeration {
+            cells,
+            cell_constraints,
+            placed: vec![0; needed.len()],
+            needed,
+            unassigned,
+            assignment: vec![false; cell_count],
+            mine_limit,
+            weights: vec![0.0; cell_count + 1],
+            cell_weights: vec![vec![0.0; cell_count]; cell_count + 1],
+        }
+    }
+
+    fn search(&mut self, pos: usize, mines: usize, steps: &mut usize) -> bool {
+        *steps += 1;
+        if *steps > PROBABILITY_STEP_BUDGET {
+            return false;
+        }
+        if pos == self.cells.len() {
+            self.weights[mines] += 1.0;
+            for (local_idx, &is_mine) in self.assignment.iter().enumerate() {
+                if is_mine {
+                    self.cell_weights[mines][local_idx] += 1.0;
+                }
+            }
+            return true;
+        }
+        for &is_mine in [false, true].iter() {
+            if is_mine && mines == self.mine_limit {
+                continue;
+            }
+            let value = is_mine as usize;
+            let fits = self.cell_constraints[pos].iter().all(|&c| {
+                let placed = self.placed[c] + value;
+                placed <= self.needed[c] && placed + self.unassigned[c] > self.needed[c]
+            });
+            if !fits {
+                continue;
+            }
+            for &c in self.cell_constraints[pos].iter() {
+                self.placed[c] += value;
+                self.unassigned[c] -= 1;
+            }
+            self.assignment[pos] = is_mine;
+            let finished = self.search(pos + 1, mines + value, steps);
+            for &c in self.cell_constraints[pos].iter() {
+                self.placed[c] -= value;
+                self.unassigned[c] += 1;
+            }
+            self.assignment[pos] = false;
+            if !finished {
+                return false;
+            }
+        }
+        true
+    }
+
+    /// Scales the counts down so that products over many components stay
+    /// within range; every layout picks one entry per component, so the
+    /// scale cancels out.
+    fn normalize(&mut self) {
+        let max = self.weights.iter().copied().fold(0.0, f64::max);
+        if max == 0.0 {
+            return;
+        }
+        for w in self.weights.iter_mut() {
+            *w /= max;
+        }
+        for cell_weights in self.cell_weights.iter_mut() {
+            for w in cell_weights.iter_mut() {
+                *w /= max;
+            }
+        }
+    }
+}
+
+/// Plays out the layout from `start` using deductions only. Returns false if
+/// a guess would be needed.
+pub fn is_solvable_without_guess(size: (usize, usize), mine_pos: &[usize], start: usize) -> bool {
+    let mut grid = SolverGrid::new(size);
+    grid.set_mine_count(Some(mine_pos.len()));
+    let mut has_mine = vec![false; grid.cells.len()];
+    for &idx in mine_pos {
+        has_mine[idx] = true;
+    }
+    if has_mine[start] {
+        return false;
+    }
+    let numbers = (0..grid.cells.len())
+        .map(|idx| grid.neighbors(idx).iter().filter(|&&n| has_mine[n]).count() as u8)
+        .collect::<Vec<_>>();
+
+    let mut rest_count = grid.cells.len() - mine_pos.len();
+    let mut pending = vec![start];
+    loop {
+        while let Some(idx) = pending.pop() {
+            if grid.cells[idx] != SolverCell::Unknown {
+                continue;
+            }
+            grid.cells[idx] = SolverCell::Open(numbers[idx]);
+            rest_count -= 1;
+            if numbers[idx] == 0 {
+                pending.extend_from_slice(grid.neighbors(idx));
+            }
+        }
+        if rest_count == 0 {
+            return true;
+        }
+        let deductions = deduce(&grid);
+        if deductions.is_empty() {
+            return false;
+        }
+        for &idx in deductions.mines.iter() {
+            grid.cells[idx] = SolverCell::Mine;
+        }
+        pending = deductions.safe;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_random;
+
+    const SIZE: (usize, usize) = (10, 11);
+
+    /// Grid for the given layout with every mine-free block accepted by
+    /// `is_open` opened.
+    fn grid_for(mine_pos: &[usize], is_open: impl Fn(usize) -> bool) -> SolverGrid {
+        let mut grid = SolverGrid::new(SIZE);
+        grid.set_mine_count(Some(mine_pos.len()));
+        for idx in 0..grid.cells().len() {
+            if mine_pos.contains(&idx) || !is_open(idx) {
+                continue;
+            }
+            let number = grid
+                .neighbors(idx)
+                .iter()
+                .filter(|n| mine_pos.contains(n))
+                .count();
+            grid.set_cell(idx, SolverCell::Open(number as u8));
+        }
+        grid
+    }
+
+    #[test]
+    fn number_alone_decides_its_neighbors() {
+        let mut neighbors = SolverGrid::new(SIZE).neighbors(0).to_vec();
+        neighbors.sort_unstable();
+
+        let mut grid = grid_for(&[], |idx| idx == 0);
+        grid.set_mine_count(None);
+        let deductions = deduce(&grid);
+        assert_eq!(deductions.safe, neighbors);
+        assert!(deductions.mines.is_empty());
+
+        let mut grid = grid_for(&neighbors, |idx| idx == 0);
+        grid.set_mine_count(None);
+        let deductions = deduce(&grid);
+        assert!(deductions.safe.is_empty());
+        assert_eq!(deductions.mines, neighbors);
+    }
+
+    #[test]
+    fn deductions_are_always_right() {
+        for seed in 0..50 {
+            let mine_pos = model_random::pick_mine_pos_list(seed, SIZE.0 * SIZE.1, 20, &[]);
+            let grid = grid_for(&mine_pos, |idx| idx < 55);
+            let deductions = deduce(&grid);
+            assert!(deductions.safe.iter().all(|idx| !mine_pos.contains(idx)));
+            assert!(deductions.mines.iter().all(|idx| mine_pos.contains(idx)));
+        }
+    }
+
+    #[test]
+    fn probabilities_agree_with_deductions() {
+        for seed in 0..20 {
+            let mine_pos = model_random::pick_mine_pos_list(seed, SIZE.0 * SIZE.1, 20, &[]);
+            let grid = grid_for(&mine_pos, |idx| idx < 55);
+            let deductions = deduce(&grid);
+            let probabilities = match mine_probabilities(&grid) {
+                Some(probabilities) => probabilities,
+                None => continue,
+            };
+            assert!(probabilities
+                .iter()
+                .all(|&p| (0.0..=1.0 + 1e-9).contains(&p)));
+            let unknown_sum = (0..probabilities.len())
+                .filter(|&idx| grid.cell(idx) == SolverCell::Unknown)
+                .map(|idx| probabilities[idx])
+                .sum::<f64>();
+            assert!((unknown_sum - mine_pos.len() as f64).abs() < 1e-6);
+            assert!(deductions.safe.iter().all(|&idx| probabilities[idx] < 1e-9));
+            assert!(deductions
+                .mines
+                .iter()
+                .all(|&idx| probabilities[idx] > 1.0 - 1e-9));
+        }
+    }
+
+    #[test]
+    fn mine_on_start_is_not_solvable() {
+        assert!(!is_solvable_without_guess(SIZE, &[0], 0));
+        assert!(is_solvable_without_guess(SIZE, &[], 0));
+    }
+}
@@ -0,0 +1,572 @@
+use crate::model_config::BoardSettingKind;
+use crate::model_storage::Storage;
+use crate::view_command::AlertFailure;
+use chrono::{DateTime, Local, SecondsFormat};
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+#[derive(Clone, Debug, Default)]
+pub struct ClickStats {
+    pub left: usize,
+    pub right: usize,
+    pub chords: usize,
+    /// Clicks of any kind that left the board unchanged.
+    pub wasted: usize,
+    /// Right clicks that placed or removed a flag.
+    pub flag_toggles: usize,
+}
+
+impl ClickStats {
+    pub fn total(&self) -> usize {
+        self.left + self.right + self.chords
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct GameStats {
+    pub won: bool,
+    pub time: Duration,
+    pub bbbv: usize,
+    pub clicks: ClickStats,
+}
+
+impl GameStats {
+    pub fn bbbv_per_second(&self) -> f64 {
+        let secs = self.time.as_secs_f64();
+        if secs > 0.0 {
+            self.bbbv as f64 / secs
+        } else {
+            0.0
+        }
+    }
+
+    /// Share of the clicks that were really needed; above 100% means
+    /// chords saved clicks.
+    pub fn efficiency(&self) -> f64 {
+        let clicks = self.clicks.total();
+        if clicks > 0 {
+            self.bbbv as f64 / clicks as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+/// How the game that produced a result was set up.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PlayMode {
+    Normal,
+    /// A loaded map, a board code or a restarted game.
+    Predefined,
+    Recording,
+}
+
+#[derive(Clone, Debug)]
+pub struct GameResult {
+    pub date: DateTime<Local>,
+    pub kind: BoardSettingKind,
+    pub mode: PlayMode,
+    pub width: usize,
+    pub height: usize,
+    pub mines: usize,
+    pub time: Duration,
+    pub won: bool,
+    pub clicks: usize,
+    pub bbbv: usize,
+    /// Board code that replays this exact mine field.
+    pub code: Option<String>,
+    /// Hints or the heatmap were used, so the time is not a fair best time.
+    pub assisted: bool,
+}
+
+impl GameResult {
+    const HEADER: &'static str =
+        "date,kind,width,height,mines,time_ms,won,clicks,bbbv,code,mode,assisted";
+
+    fn kind_name(kind: BoardSettingKind) -> &'static str {
+        match kind {
+            BoardSettingKind::Easy => "easy",
+            BoardSettingKind::Normal => "normal",
+            BoardSettingKind::Hard => "hard",
+            BoardSettingKind::Custom => "custom",
+        }
+    }
+
+    fn mode_name(mode: PlayMode) -> &'static str {
+        match mode {
+            PlayMode::Normal => "normal",
+            PlayMode::Predefined => "predefined",
+            PlayMode::Recording => "recording",
+        }
+    }
+
+    fn to_line(&self) -> String {
+        format!(
+            "{},{},{},{},{},{},{},{},{},{},{},{}",
+            self.date.to_rfc3339_opts(SecondsFormat::Secs, false),
+            Self::kind_name(self.kind),
+            self.width,
+            self.height,
+            self.mines,
+            self.time.as_millis(),
+            self.won,
+            self.clicks,
+            self.bbbv,
+            self.code.as_deref().unwrap_or(""),
+            Self::mode_name(self.mode),
+            self.assisted
+        )
+    }
+
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"date\": {}, \"kind\": {}, \"width\": {}, \"height\": {}, \"mines\": {}, \
+             \"time_ms\": {}, \"won\": {}, \"clicks\": {}, \"bbbv\": {}, \"code\": {}, \
+             \"mode\": {}, \"assisted\": {}}}",
+            json_string(&self.date.to_rfc3339_opts(SecondsFormat::Secs, false)),
+            json_string(Self::kind_name(self.kind)),
+            self.width,
+            self.height,
+            self.mines,
+            self.time.as_millis(),
+            self.won,
+            self.clicks,
+            self.bbbv,
+            match &self.code {
+                Some(code) => json_string(code),
+                None => "null".to_owned(),
+            },
+            json_string(Self::mode_name(self.mode)),
+            self.assisted
+        )
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let fields = line.trim().split(',').collect::<Vec<_>>();
+        // older files have 9 columns, or 10 with the board code; their games
+        // were played normally as far as anyone can tell.
+        if fields.len() != 9 && fields.len() != 10 && fields.len() != 12 {
+            return None;
+        }
+        let kind = match fields[1] {
+            "easy" => BoardSettingKind::Easy,
+            "normal" => BoardSettingKind::Normal,
+            "hard" => BoardSettingKind::Hard,
+            "custom" => BoardSettingKind::Custom,
+            _ => return None,
+        };
+        let mode = match fields.get(10) {
+            None | Some(&"normal") => PlayMode::Normal,
+            Some(&"predefined") => PlayMode::Predefined,
+            Some(&"recording") => PlayMode::Recording,
+            Some(_) => return None,
+        };
+        Some(GameResult {
+            date: DateTime::parse_from_rfc3339(fields[0])
+                .ok()?
+                .with_timezone(&Local),
+            kind,
+            mode,
+            width: fields[2].parse().ok()?,
+            height: fields[3].parse().ok()?,
+            mines: fields[4].parse().ok()?,
+            time: Duration::from_millis(fields[5].parse().ok()?),
+            won: fields[6].parse().ok()?,
+            clicks: fields[7].parse().ok()?,
+            bbbv: fields[8].parse().ok()?,
+            code: fields
+                .get(9)
+                .filter(|code| !code.is_empty())
+                .map(|code| code.to_string()),
+            assisted: match fields.get(11) {
+                Some(assisted) => assisted.parse().ok()?,
+                None => false,
+            },
+        })
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct HistorySummary {
+    pub played: usize,
+    pub won: usize,
+    pub current_streak: usize,
+    pub best_streak: usize,
+    pub average_time: Option<Duration>,
+    pub best_time: Option<Duration>,
+}
+
+impl HistorySummary {
+    const HEADER: &'static str =
+        "kind,played,won,win_rate,current_streak,best_streak,average_time_ms,best_time_ms";
+
+    pub fn win_rate(&self) -> f64 {
+        if self.played > 0 {
+            self.won as f64 / self.played as f64
+        } else {
+            0.0
+        }
+    }
+
+    fn to_line(&self, kind: BoardSettingKind) -> String {
+        let millis = |time: Option<Duration>| time.map(|time| time.as_millis().to_string());
+        format!(
+            "{},{},{},{:.4},{},{},{},{}",
+            GameResult::kind_name(kind),
+            self.played,
+            self.won,
+            self.win_rate(),
+            self.current_streak,
+            self.best_streak,
+            millis(self.average_time).unwrap_or_default(),
+            millis(self.best_time).unwrap_or_default()
+        )
+    }
+
+    fn to_json(&self) -> String {
+        let millis = |time: Option<Duration>| match time {
+            Some(time) => time.as_millis().to_string(),
+            None => "null".to_owned(),
+        };
+        format!(
+            "{{\"played\": {}, \"won\": {}, \"win_rate\": {:.4}, \"current_streak\": {}, \
+             \"best_streak\": {}, \"average_time_ms\": {}, \"best_time_ms\": {}}}",
+            self.played,
+            self.won,
+            self.win_rate(),
+            self.current_streak,
+            self.best_streak,
+            millis(self.average_time),
+            millis(self.best_time)
+        )
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ExportFormat {
+    Csv,
+    Json,
+}
+
+impl ExportFormat {
+    /// Anything but a `.json` file is written as CSV.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|ext| ext.to_str()) {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ExportFormat::Json,
+            _ => ExportFormat::Csv,
+        }
+    }
+}
+
+fn json_string(s: &str) -> String {
+    let mut result = String::with_capacity(s.len() + 2);
+    result.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => result.push_str("\\\""),
+            '\\' => result.push_str("\\\\"),
+            c if (c as u32) < 0x20 => result.push_str(&format!("\\u{:04x}", c as u32)),
+            c => result.push(c),
+        }
+    }
+    result.push('"');
+    result
+}
+
+/// Every finished game, oldest first. Results are appended to a CSV file one
+/// line at a time, so the history survives crashes and stays readable by
+/// other tools.
+#[derive(Clone, Debug, Default)]
+pub struct GameHistory {
+    results: Vec<GameResult>,
+    path: Option<PathBuf>,
+}
+
+impl GameHistory {
+    const FILE_NAME: &'static str = "statistics.csv";
+
+    pub fn load(storage: &Storage) -> Self {
+        let mut history = GameHistory {
+            results: Vec::new(),
+            path: storage.data_file(Self::FILE_NAME),
+        };
+        if let Some(content) = history
+            .path
+            .as_ref()
+            .and_then(|path| fs::read_to_string(path).ok())
+        {
+            history.results = content.lines().filter_map(GameResult::from_line).collect();
+            // rows are appended under the header already in the file, so a
+            // file from an older version is brought up to date first.
+            if !content.is_empty() && content.lines().next() != Some(GameResult::HEADER) {
+                let _ = history.rewrite();
+            }
+        }
+        history
+    }
+
+    fn rewrite(&self) -> io::Result<()> {
+        let path = match self.path.as_ref() {
+            Some(path) => path,
+            None => return Ok(()),
+        };
+        let mut content = format!("{}\n", GameResult::HEADER);
+        for result in self.results.iter() {
+            content.push_str(&result.to_line());
+            content.push('\n');
+        }
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, path)
+    }
+
+    pub fn results(&self) -> &[GameResult] {
+        &self.results
+    }
+
+    pub fn append(&mut self, result: GameResult) -> Result<(), AlertFailure> {
+        let line = result.to_line();
+        self.results.push(result);
+        let path = self.path.as_ref().ok_or(AlertFailure::FileIOError)?;
+        Self::append_line(path, &line).map_err(|_| AlertFailure::FileIOError)
+    }
+
+    fn append_line(path: &Path, line: &str) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+        if is_new {
+            writeln!(file, "{}", GameResult::HEADER)?;
+        }
+        writeln!(file, "{}", line)
+    }
+
+    pub fn reset(&mut self) -> Result<(), AlertFailure> {
+        self.results.clear();
+        let path = self.path.as_ref().ok_or(AlertFailure::FileIOError)?;
+        match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(AlertFailure::FileIOError),
+            _ => Ok(()),
+        }
+    }
+
+    const SUMMARY_KINDS: [BoardSettingKind; 4] = [
+        BoardSettingKind::Easy,
+        BoardSettingKind::Normal,
+        BoardSettingKind::Hard,
+        BoardSettingKind::Custom,
+    ];
+
+    /// CSV output holds two tables, every game and then the per-difficulty
+    /// aggregates, separated by an empty line.
+    pub fn export(&self, format: ExportFormat) -> String {
+        let mut output = String::new();
+        match format {
+            ExportFormat::Csv => {
+                output.push_str(GameResult::HEADER);
+                output.push('\n');
+                for result in self.results.iter() {
+                    output.push_str(&result.to_line());
+                    output.push('\n');
+                }
+                output.push('\n');
+                output.push_str(HistorySummary::HEADER);
+                output.push('\n');
+                for &kind in Self::SUMMARY_KINDS.iter() {
+                    output.push_str(&self.summary(kind).to_line(kind));
+                    output.push('\n');
+                }
+            }
+            ExportFormat::Json => {
+                output.push_str("{\n  \"games\": [");
+                for (idx, result) in self.results.iter().enumerate() {
+                    output.push_str(if idx == 0 { "\n    " } else { ",\n    " });
+                    output.push_str(&result.to_json());
+                }
+                output.push_str(if self.results.is_empty() {
+                    "],\n"
+                } else {
+                    "\n  ],\n"
+                });
+                output.push_str("  \"summary\": {");
+                for (idx, &kind) in Self::SUMMARY_KINDS.iter().enumerate() {
+                    output.push_str(if idx == 0 { "\n    " } else { ",\n    " });
+                    output.push_str(&format!(
+                        "{}: {}",
+                        json_string(GameResult::kind_name(kind)),
+                        self.summary(kind).to_json()
+                    ));
+                }
+                output.push_str("\n  }\n}\n");
+            }
+        }
+        output
+    }
+
+    pub fn export_to_file(&self, path: &Path) -> Result<(), AlertFailure> {
+        let output = self.export(ExportFormat::from_path(path));
+        fs::write(path, output).map_err(|_| AlertFailure::FileIOError)
+    }
+
+    pub fn summary(&self, kind: BoardSettingKind) -> HistorySummary {
+        let mut summary = HistorySummary::default();
+        let mut total_time = Duration::default();
+        for result in self.results.iter().filter(|result| result.kind == kind) {
+            summary.played += 1;
+            if !result.won {
+                summary.current_streak = 0;
+                continue;
+            }
+            summary.won += 1;
+            summary.current_streak += 1;
+            summary.best_streak = summary.best_streak.max(summary.current_streak);
+            total_time += result.time;
+            if result.assisted {
+                continue;
+            }
+            summary.best_time = Some(match summary.best_time {
+                Some(best_time) => best_time.min(result.time),
+                None => result.time,
+            });
+        }
+        if summary.won > 0 {
+            summary.average_time = Some(total_time / summary.won as u32);
+        }
+        summary
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_storage::ScratchDir;
+
+    fn result(kind: BoardSettingKind, won: bool, millis: u64) -> GameResult {
+        GameResult {
+            date: Local::now(),
+            kind,
+            mode: PlayMode::Normal,
+            width: 11,
+            height: 10,
+            mines: 10,
+            time: Duration::from_millis(millis),
+            won,
+            clicks: 20,
+            bbbv: 15,
+            code: None,
+            assisted: false,
+        }
+    }
+
+    #[test]
+    fn result_line_roundtrip() {
+        let mut saved = result(BoardSettingKind::Hard, true, 12345);
+        saved.code = Some("ABC123".to_owned());
+        saved.mode = PlayMode::Predefined;
+        saved.assisted = true;
+        let loaded = GameResult::from_line(&saved.to_line()).unwrap();
+        assert_eq!(loaded.to_line(), saved.to_line());
+        assert_eq!(loaded.time, Duration::from_millis(12345));
+        assert_eq!(loaded.code.as_deref(), Some("ABC123"));
+        assert_eq!(loaded.mode, PlayMode::Predefined);
+        assert!(loaded.assisted);
+
+        let loaded = GameResult::from_line(&result(BoardSettingKind::Easy, false, 5).to_line());
+        assert!(loaded.unwrap().code.is_none());
+        assert!(GameResult::from_line(GameResult::HEADER).is_none());
+    }
+
+    #[test]
+    fn older_result_lines_are_still_read() {
+        let old_line = "2024-01-02T03:04:05+00:00,easy,11,10,10,5000,false,7,12";
+        let loaded = GameResult::from_line(old_line).unwrap();
+        assert!(!loaded.won);
+        assert!(loaded.code.is_none());
+        assert_eq!(loaded.mode, PlayMode::Normal);
+        assert!(!loaded.assisted);
+
+        let coded_line = "2024-01-02T03:04:05+00:00,hard,30,16,99,5000,true,70,120,ABC123";
+        let loaded = GameResult::from_line(coded_line).unwrap();
+        assert_eq!(loaded.code.as_deref(), Some("ABC123"));
+        assert_eq!(loaded.mode, PlayMode::Normal);
+        assert!(!loaded.assisted);
+    }
+
+    #[test]
+    fn older_history_file_is_brought_up_to_date() {
+        let dir = ScratchDir::new("history");
+        let storage = dir.storage();
+        let path = storage.data_file(GameHistory::FILE_NAME).unwrap();
+        fs::write(
+            &path,
+            "date,kind,width,height,mines,time_ms,won,clicks,bbbv,code\n\
+             2024-01-02T03:04:05+00:00,easy,11,10,10,5000,true,7,12\n\
+             2024-01-03T03:04:05+00:00,easy,11,10,10,4000,true,9,12,ABC123\n",
+        )
+        .unwrap();
+
+        let mut history = GameHistory::load(&storage);
+        assert_eq!(history.results().len(), 2);
+        history
+            .append(result(BoardSettingKind::Easy, false, 100))
+            .unwrap();
+
+        let content = fs::read_to_string(&path).unwrap();
+        assert_eq!(content.lines().next(), Some(GameResult::HEADER));
+        assert!(content
+            .lines()
+            .skip(1)
+            .all(|line| line.split(',').count() == 12));
+        let history = GameHistory::load(&storage);
+        assert_eq!(history.results().len(), 3);
+        assert_eq!(history.results()[1].code.as_deref(), Some("ABC123"));
+    }
+
+    #[test]
+    fn summary_tracks_streaks_and_times() {
+        let mut history = GameHistory::default();
+        for &(won, millis) in &[(true, 3000), (true, 1000), (false, 500), (true, 2000)] {
+            history
+                .results
+                .push(result(BoardSettingKind::Easy, won, millis));
+        }
+        history
+            .results
+            .push(result(BoardSettingKind::Normal, false, 100));
+        let mut assisted = result(BoardSettingKind::Easy, true, 200);
+        assisted.assisted = true;
+        history.results.push(assisted);
+
+        let summary = history.summary(BoardSettingKind::Easy);
+        assert_eq!((summary.played, summary.won), (5, 4));
+        assert_eq!((summary.current_streak, summary.best_streak), (2, 2));
+        assert_eq!(summary.best_time, Some(Duration::from_millis(1000)));
+        assert_eq!(summary.average_time, Some(Duration::from_millis(1550)));
+
+        let summary = history.summary(BoardSettingKind::Hard);
+        assert_eq!(summary.played, 0);
+        assert_eq!(summary.win_rate(), 0.0);
+        assert!(summary.average_time.is_none());
+    }
+
+    #[test]
+    fn json_strings_are_escaped() {
+        assert_eq!(json_string("a\"b\\c\n"), "\"a\\\"b\\\\c\\u000a\"");
+    }
+
+    #[test]
+    fn export_format_follows_extension() {
+        assert_eq!(
+            ExportFormat::from_path(Path::new("stats.JSON")),
+            ExportFormat::Json
+        );
+        assert_eq!(
+            ExportFormat::from_path(Path::new("stats.txt")),
+            ExportFormat::Csv
+        );
+    }
+}
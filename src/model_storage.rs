@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+#[cfg(windows)]
+pub fn data_dir() -> Option<PathBuf> {
+    env::var_os("APPDATA").map(|dir| PathBuf::from(dir).join("CharlesMine"))
+}
+
+#[cfg(not(windows))]
+pub fn data_dir() -> Option<PathBuf> {
+    env::var_os("XDG_DATA_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")))
+        .map(|dir| dir.join("charlesmine"))
+}
+
+#[cfg(windows)]
+pub fn config_dir() -> Option<PathBuf> {
+    data_dir()
+}
+
+#[cfg(not(windows))]
+pub fn config_dir() -> Option<PathBuf> {
+    env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| env::var_os("HOME").map(|home| Path::new(&home).join(".config")))
+        .map(|dir| dir.join("charlesmine"))
+}
+
+/// Where the configuration and data files are kept.
+#[derive(Clone, Debug)]
+pub struct Storage {
+    config_dir: Option<PathBuf>,
+    data_dir: Option<PathBuf>,
+}
+
+impl Storage {
+    /// The per-user directories of the platform.
+    pub fn user_dirs() -> Self {
+        Storage {
+            config_dir: config_dir(),
+            data_dir: data_dir(),
+        }
+    }
+
+    /// Keeps every file directly in `dir`.
+    pub fn in_dir(dir: &Path) -> Self {
+        Storage {
+            config_dir: Some(dir.to_owned()),
+            data_dir: Some(dir.to_owned()),
+        }
+    }
+
+    pub fn config_file(&self, file_name: &str) -> Option<PathBuf> {
+        self.config_dir.as_ref().map(|dir| dir.join(file_name))
+    }
+
+    pub fn data_file(&self, file_name: &str) -> Option<PathBuf> {
+        self.data_dir.as_ref().map(|dir| dir.join(file_name))
+    }
+}
+
+/// A fresh directory for a test, removed again when dropped.
+#[cfg(test)]
+pub(crate) struct ScratchDir(PathBuf);
+
+#[cfg(test)]
+impl ScratchDir {
+    pub(crate) fn new(name: &str) -> Self {
+        let dir = env::temp_dir().join(format!("charlesmine-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        ScratchDir(dir)
+    }
+
+    pub(crate) fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub(crate) fn storage(&self) -> Storage {
+        Storage::in_dir(&self.0)
+    }
+}
+
+#[cfg(test)]
+impl Drop for ScratchDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct KeyValueFile {
+    entries: BTreeMap<String, String>,
+}
+
+impl KeyValueFile {
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Missing files and malformed lines are skipped rather than reported, so
+    /// that a damaged file degrades to default values.
+    pub fn load(path: &Path) -> Self {
+        let mut file = KeyValueFile::new();
+        let content = match fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(_) => return file,
+        };
+        for line in content.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(eq_pos) = line.find('=') {
+                let key = line[..eq_pos].trim();
+                let value = line[eq_pos + 1..].trim();
+                if !key.is_empty() {
+                    file.entries.insert(key.to_owned(), value.to_owned());
+                }
+            }
+        }
+        file
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let mut content = String::new();
+        for (key, value) in self.entries.iter() {
+            content.push_str(key);
+            content.push('=');
+            content.push_str(value);
+            content.push('\n');
+        }
+        let temp_path = path.with_extension("tmp");
+        fs::write(&temp_path, content)?;
+        fs::rename(&temp_path, path)
+    }
+
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.entries.get(key).map(|v| v.as_str())
+    }
+
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Option<T> {
+        self.get(key).and_then(|v| v.parse().ok())
+    }
+
+    pub fn set<T: ToString>(&mut self, key: &str, value: T) {
+        let value = value
+            .to_string()
+            .chars()
+            .filter(|c| !c.is_control())
+            .collect::<String>();
+        self.entries.insert(key.to_owned(), value.trim().to_owned());
+    }
+
+    pub fn remove(&mut self, key: &str) {
+        self.entries.remove(key);
+    }
+}
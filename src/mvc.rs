@@ -0,0 +1,49 @@
+use crate::controller::Controller;
+use crate::model::{Model, ModelCommand};
+use crate::view::{View, ViewCommand};
+use std::ops;
+
+pub type GameMVC = domino::mvc::MVCSystem<GameModel, View, Controller>;
+
+/// Hooks the platform-independent `Model` up to the MVC system.
+pub struct GameModel(Model);
+
+impl GameModel {
+    pub fn new() -> Self {
+        GameModel(Model::new())
+    }
+}
+
+impl ops::Deref for GameModel {
+    type Target = Model;
+
+    fn deref(&self) -> &Model {
+        &self.0
+    }
+}
+
+impl ops::DerefMut for GameModel {
+    fn deref_mut(&mut self) -> &mut Model {
+        &mut self.0
+    }
+}
+
+type ModelToken<'a> = ::domino::mvc::ModelToken<'a, GameModel, View, Controller>;
+
+impl ::domino::mvc::Model<View, Controller> for GameModel {
+    type Command = ModelCommand;
+    type Notification = ViewCommand;
+
+    fn process_command(mut token: ModelToken, command: ModelCommand) {
+        let view_commands = token.model_mut().0.process_command(command);
+        for view_command in view_commands {
+            token.update_view_next(view_command);
+        }
+    }
+
+    fn translate_controller_notification(
+        controller_notification: ModelCommand,
+    ) -> Option<Self::Command> {
+        Some(controller_notification)
+    }
+}
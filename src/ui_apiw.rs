@@ -9,10 +9,12 @@ use crate::THE_GAME;
 
 use crate::controller::{self, ControllerInput};
 use crate::model;
+use crate::model_hero;
 use crate::view;
 use crate::view::ViewCommand;
 use crate::view_assets;
 use apiw::application_support_functions::MessageBoxBuilder;
+use apiw::dialog_proc;
 use apiw::timer_proc;
 use apiw::window_proc;
 use domino::mvc::ViewToken;
@@ -20,25 +22,235 @@ use domino::mvc::ViewToken;
 pub type UiWindow = apiw::windows_subsystem::window::ForeignWindow;
 pub type UiScopedDC<'a> = apiw::graphics_subsystem::device_context::ScopedDeviceContext<'a>;
 pub type UiLocalDC = apiw::graphics_subsystem::device_context::LocalDeviceContext;
-pub type UiResult<T> = apiw::Result<T>;
-
-pub type Size = apiw::graphics_subsystem::Size;
-pub type Point = apiw::graphics_subsystem::Point;
-pub type Rect = apiw::graphics_subsystem::Rect;
-pub type RGBColor = apiw::graphics_subsystem::RGBColor;
 
 use crate::model_config;
+use crate::ui_backend::{Point, RGBColor, Size, UiBackend, UiCanvas, UiError, UiResult};
 use apiw::application_support_functions::OpenFileDialogBuilder;
 use apiw::application_support_functions::OpenFileDialogFlags;
 use apiw::application_support_functions::SaveFileDialogBuilder;
 use apiw::application_support_functions::SaveFileDialogFlags;
-pub use apiw::graphics_subsystem::draw::Draw as UiDraw;
+use apiw::graphics_subsystem::object::Bitmap;
+use std::cell::RefCell;
 use std::path::PathBuf;
+use std::time::Duration;
+
+fn ui_result<T>(result: apiw::Result<T>) -> UiResult<T> {
+    result.map_err(UiError::new)
+}
+
+fn apiw_point(point: Point) -> apiw::graphics_subsystem::Point {
+    apiw::graphics_subsystem::Point::new(point.x(), point.y())
+}
+
+fn apiw_size(size: Size) -> apiw::graphics_subsystem::Size {
+    apiw::graphics_subsystem::Size::new(size.cx(), size.cy())
+}
+
+fn apiw_color(color: RGBColor) -> apiw::graphics_subsystem::RGBColor {
+    apiw::graphics_subsystem::RGBColor::new(color.r(), color.g(), color.b())
+}
+
+fn format_seconds(time: Option<std::time::Duration>) -> String {
+    match time {
+        Some(time) => format!("{:.3} s", time.as_secs_f64()),
+        None => "-".to_owned(),
+    }
+}
 
 pub fn ui_alert(msg: &str) {
     MessageBoxBuilder::new().message(msg).invoke().unwrap();
 }
 
+thread_local! {
+    static DIALOG_FIELDS: RefCell<(Vec<(isize, String)>, Vec<isize>)> =
+        RefCell::new((Vec::new(), Vec::new()));
+    static DEFERRED_COMMANDS: RefCell<Vec<model::ModelCommand>> = RefCell::new(Vec::new());
+    static TIMER_HANDLER: RefCell<Option<fn()>> = RefCell::new(None);
+    static BITMAPS: RefCell<Vec<(isize, Bitmap)>> = RefCell::new(Vec::new());
+    static PIXEL_DCS: RefCell<Vec<(isize, UiLocalDC)>> = RefCell::new(Vec::new());
+}
+
+/// Takes a loaded instance of the bitmap resource out of the pool; hand it
+/// back with `reuse_bitmap_instance` once it is no longer selected.
+fn bitmap_instance(bitmap_id: isize) -> Bitmap {
+    BITMAPS.with(|bitmaps| {
+        let mut bitmaps = bitmaps.borrow_mut();
+        match bitmaps.iter().position(|(id, _)| *id == bitmap_id) {
+            Some(idx) => bitmaps.swap_remove(idx).1,
+            None => Bitmap::load_from_resource_id(bitmap_id as _)
+                .expect("Failed to load resource bitmap."),
+        }
+    })
+}
+
+fn reuse_bitmap_instance(bitmap_id: isize, bitmap: Bitmap) {
+    BITMAPS.with(|bitmaps| bitmaps.borrow_mut().push((bitmap_id, bitmap)));
+}
+
+fn timer_handler(_request: apiw::windows_subsystem::window::TimerProcRequest) {
+    if let Some(handler) = TIMER_HANDLER.with(|handler| *handler.borrow()) {
+        handler();
+    }
+}
+
+/// Paints into a device context, usually the one of a `WM_PAINT`.
+pub struct Canvas<'a, 'b> {
+    dc: &'a mut UiScopedDC<'b>,
+}
+
+impl<'a, 'b> Canvas<'a, 'b> {
+    pub fn new(dc: &'a mut UiScopedDC<'b>) -> Self {
+        Canvas { dc }
+    }
+}
+
+impl<'a, 'b> UiCanvas for Canvas<'a, 'b> {
+    fn set_zoom(&mut self, ratio: usize) -> UiResult<()> {
+        use apiw::extensions::draw_ext::{GraphicsMode, Transform};
+
+        let v = ratio as f32;
+        ui_result(self.dc.set_graphics_mode(GraphicsMode::ADVANCED))?;
+        ui_result(
+            self.dc
+                .set_world_transform(&Transform::new_with_values(&[v, 0.0, 0.0, v, 0.0, 0.0])),
+        )?;
+        Ok(())
+    }
+
+    fn draw_polyline(&mut self, points: &[Point], width: usize, color: RGBColor) -> UiResult<()> {
+        use apiw::graphics_subsystem::object::PenBuilder;
+
+        let (first, rest) = match points.split_first() {
+            Some(v) => v,
+            None => return Ok(()),
+        };
+        let pen = ui_result(
+            PenBuilder::new()
+                .width(width)
+                .color(apiw_color(color))
+                .create(),
+        )?;
+        ui_result(self.dc.select_pen(pen))?;
+        ui_result(self.dc.move_to(apiw_point(*first)))?;
+        for point in rest {
+            ui_result(self.dc.line_to(apiw_point(*point)))?;
+        }
+        Ok(())
+    }
+
+    fn draw_sprite(
+        &mut self,
+        bitmap_id: isize,
+        src_pos: Point,
+        size: Size,
+        dest_pos: Point,
+        transparent_key: Option<RGBColor>,
+    ) -> UiResult<()> {
+        use apiw::graphics_subsystem::TenaryROP;
+
+        let mut memory_dc = ui_result(UiLocalDC::new_compatible_memory_dc(self.dc))?;
+        let bitmap = bitmap_instance(bitmap_id);
+        let (src_pos, dest_pos, size) =
+            (apiw_point(src_pos), apiw_point(dest_pos), apiw_size(size));
+        let result = (|| -> apiw::Result<()> {
+            memory_dc.select_bitmap(bitmap.clone())?;
+            match transparent_key {
+                Some(key) => self.dc.transparentblt(
+                    &memory_dc,
+                    src_pos,
+                    size,
+                    dest_pos,
+                    size,
+                    apiw_color(key),
+                )?,
+                None => self
+                    .dc
+                    .bitblt(&memory_dc, src_pos, dest_pos, size, TenaryROP::SRCCOPY)?,
+            };
+            Ok(())
+        })();
+        memory_dc.reset_to_initial_state();
+        reuse_bitmap_instance(bitmap_id, bitmap);
+        ui_result(result)
+    }
+}
+
+/// Shows a dialog resource with the given controls prefilled. Returns the id
+/// of the button that closed it along with the final control texts.
+pub fn ui_dialog(
+    parent: &UiWindow,
+    dialog_res_id: isize,
+    fields: Vec<(isize, String)>,
+    extra_buttons: &[isize],
+) -> apiw::Result<(isize, Vec<String>)> {
+    use apiw::windows_subsystem::dialog::DialogBuilder;
+    use apiw::windows_subsystem::dialog::DialogProcRequest;
+    use apiw::windows_subsystem::dialog::ForeignDialog;
+    use apiw::windows_subsystem::window::CommandEventArgs;
+
+    fn dialog_handler(request: DialogProcRequest) {
+        use crate::view_assets::resources;
+
+        request
+            .route_init_dialog(|dialog: &ForeignDialog| -> apiw::Result<bool> {
+                DIALOG_FIELDS.with(|state| {
+                    for (item_id, text) in state.borrow().0.iter() {
+                        dialog.item(*item_id as _)?.set_text(text)?;
+                    }
+                    Ok(true)
+                })
+            })
+            .route_command(
+                |dialog: &ForeignDialog, args: CommandEventArgs| -> apiw::Result<()> {
+                    let id = args.id() as isize;
+                    if id == resources::IDOK {
+                        DIALOG_FIELDS.with(|state| -> apiw::Result<()> {
+                            for (item_id, text) in state.borrow_mut().0.iter_mut() {
+                                *text = dialog.item(*item_id as _)?.text()?;
+                            }
+                            Ok(())
+                        })?;
+                        dialog.end_dialog(id as _)?;
+                    } else if id == resources::IDCANCEL
+                        || DIALOG_FIELDS.with(|state| state.borrow().1.contains(&id))
+                    {
+                        dialog.end_dialog(id as _)?;
+                    }
+                    Ok(())
+                },
+            );
+    }
+
+    DIALOG_FIELDS.with(|state| *state.borrow_mut() = (fields, extra_buttons.to_vec()));
+    let result = DialogBuilder::new_from_resource_id(dialog_res_id as _)
+        .parent(parent)
+        .dialog_proc(dialog_proc!(dialog_handler))
+        .invoke()?;
+    let (fields, _) = DIALOG_FIELDS.with(|state| state.replace((Vec::new(), Vec::new())));
+    Ok((
+        result as isize,
+        fields.into_iter().map(|(_, text)| text).collect(),
+    ))
+}
+
+pub fn ui_edit_dialog(
+    parent: &UiWindow,
+    dialog_res_id: isize,
+    fields: Vec<(isize, String)>,
+) -> apiw::Result<Option<Vec<String>>> {
+    let (result, values) = ui_dialog(parent, dialog_res_id, fields, &[])?;
+    if result != crate::view_assets::resources::IDOK {
+        return Ok(None);
+    }
+    Ok(Some(values))
+}
+
+/// Queues a command to be processed once the current one has finished, for
+/// use while the game is already borrowed (e.g. from within the view).
+pub fn ui_defer_command(command: model::ModelCommand) {
+    DEFERRED_COMMANDS.with(|commands| commands.borrow_mut().push(command));
+}
+
 pub struct Ui;
 
 impl Ui {
@@ -103,6 +315,15 @@ impl Ui {
             .show_dialog()
             .expect("Error occurred")
     }
+    fn process_model_command(command: model::ModelCommand) -> apiw::Result<()> {
+        THE_GAME.with(|game| {
+            let mut game = game.try_borrow_mut().or_else(|_| apiw::internal_error())?;
+            let game = &mut *game;
+            game.mvc.process_input(ControllerInput::ModelCommand(command));
+            Ok(())
+        })
+    }
+
     /*
 
     BOOL HandleMapFile(bool bSave, UINT nFilterResID, LPCTSTR lpszDefExt, LPTSTR lpszFile)
@@ -150,6 +371,7 @@ impl Ui {
         fn main_window_handler(mut request: WindowProcRequest) {
             use apiw::windows_subsystem::window::CommandEventArgs;
             use apiw::windows_subsystem::window::MouseEventArgs;
+            use apiw::windows_subsystem::window::SizeEventArgs;
 
             request
                 .route_create(|window: &ForeignWindow, _| -> apiw::Result<bool> {
@@ -184,6 +406,7 @@ impl Ui {
 
                             let mut target = None;
                             if let Some(point) = mouse_args.cursor_coordinate() {
+                                let point = Point::new(point.x(), point.y());
                                 target = Some(game.mvc.view().hit_test(point));
                             }
 
@@ -266,6 +489,52 @@ impl Ui {
                                     Ok(())
                                 })?;
                             }
+                            resources::IDM_FILE_GAME_CUSTOM => {
+                                let board_setting = THE_GAME.with(|game| {
+                                    let game =
+                                        game.try_borrow().or_else(|_| apiw::internal_error())?;
+                                    Ok(game.mvc.model().config().board_setting.clone())
+                                })?;
+                                let mut fields = vec![
+                                    (resources::IDC_EDIT1, board_setting.y.to_string()),
+                                    (resources::IDC_EDIT2, board_setting.x.to_string()),
+                                    (resources::IDC_EDIT3, board_setting.c.to_string()),
+                                ];
+                                while let Some(values) = ui_edit_dialog(
+                                    window,
+                                    resources::IDD_CUSTOM_GAME,
+                                    fields.clone(),
+                                )? {
+                                    let parsed = values
+                                        .iter()
+                                        .map(|v| v.trim().parse::<usize>())
+                                        .collect::<Result<Vec<_>, _>>();
+                                    let (y, x, c) = match parsed.as_ref().map(|v| &v[..]) {
+                                        Ok(&[y, x, c]) => (y, x, c),
+                                        _ => {
+                                            ui_alert("Please enter whole numbers for height, width and mines.");
+                                            for (field, value) in fields.iter_mut().zip(values) {
+                                                field.1 = value;
+                                            }
+                                            continue;
+                                        }
+                                    };
+                                    let board_setting =
+                                        model_config::BoardSetting::new_custom(x, y, c);
+                                    if (board_setting.y, board_setting.x, board_setting.c)
+                                        != (y, x, c)
+                                    {
+                                        ui_alert(&format!(
+                                            "The values were adjusted to fit the allowed range: height {}, width {}, mines {}.",
+                                            board_setting.y, board_setting.x, board_setting.c
+                                        ));
+                                    }
+                                    Ui::process_model_command(ModelCommand::NewGameWithBoard(
+                                        board_setting,
+                                    ))?;
+                                    break;
+                                }
+                            }
                             resources::IDM_FILE_MARK => {
                                 THE_GAME.with(|game| {
                                     let mut game =
@@ -278,6 +547,27 @@ impl Ui {
                                     Ok(())
                                 })?;
                             }
+                            resources::IDM_FILE_PAUSE => {
+                                Ui::process_model_command(ModelCommand::TogglePause)?;
+                            }
+                            resources::IDM_ADVANCED_UNDO => {
+                                Ui::process_model_command(ModelCommand::Undo)?;
+                            }
+                            resources::IDM_ADVANCED_REDO => {
+                                Ui::process_model_command(ModelCommand::Redo)?;
+                            }
+                            resources::IDM_ADVANCED_HINT => {
+                                Ui::process_model_command(ModelCommand::Hint)?;
+                            }
+                            resources::IDM_ADVANCED_HEATMAP => {
+                                Ui::process_model_command(ModelCommand::ToggleHeatmap)?;
+                            }
+                            resources::IDM_FILE_NO_GUESS => {
+                                Ui::process_model_command(ModelCommand::ToggleNoGuess)?;
+                            }
+                            resources::IDM_FILE_SAFE_START => {
+                                Ui::process_model_command(ModelCommand::ToggleSafeStart)?;
+                            }
                             resources::IDM_ADVANCED_ZOOM_1x
                             | resources::IDM_ADVANCED_ZOOM_2x
                             | resources::IDM_ADVANCED_ZOOM_3x => {
@@ -303,6 +593,111 @@ impl Ui {
                                     Ok(())
                                 })?;
                             }
+                            resources::IDM_FILE_HERO_LIST => loop {
+                                let fields = THE_GAME.with(|game| {
+                                    use crate::model_config::BoardSettingKind;
+
+                                    let game =
+                                        game.try_borrow().or_else(|_| apiw::internal_error())?;
+                                    let hero_list = game.mvc.model().hero_list();
+                                    let mut fields = Vec::new();
+                                    for &(kind, time_id, name_id) in &[
+                                        (
+                                            BoardSettingKind::Easy,
+                                            resources::IDC_EDIT1,
+                                            resources::IDC_EXTRA1,
+                                        ),
+                                        (
+                                            BoardSettingKind::Normal,
+                                            resources::IDC_EDIT2,
+                                            resources::IDC_EXTRA2,
+                                        ),
+                                        (
+                                            BoardSettingKind::Hard,
+                                            resources::IDC_EDIT3,
+                                            resources::IDC_EXTRA3,
+                                        ),
+                                    ] {
+                                        let (time, name) = match hero_list.record(kind) {
+                                            Some(record) => (
+                                                format!(
+                                                    "{:.3} seconds",
+                                                    record.time.as_secs_f64()
+                                                ),
+                                                record.name.clone(),
+                                            ),
+                                            None => (
+                                                "999 seconds".to_owned(),
+                                                model_hero::HeroList::DEFAULT_NAME.to_owned(),
+                                            ),
+                                        };
+                                        fields.push((time_id, time));
+                                        fields.push((name_id, name));
+                                    }
+                                    Ok(fields)
+                                })?;
+                                let (result, _) = ui_dialog(
+                                    window,
+                                    resources::IDD_HERO_LIST,
+                                    fields,
+                                    &[resources::IDC_BUTTON1],
+                                )?;
+                                if result != resources::IDC_BUTTON1 {
+                                    break;
+                                }
+                                Ui::process_model_command(ModelCommand::ResetHeroList)?;
+                            },
+                            resources::IDM_FILE_STATISTICS => loop {
+                                let fields = THE_GAME.with(|game| {
+                                    use crate::model_config::BoardSettingKind;
+
+                                    let game =
+                                        game.try_borrow().or_else(|_| apiw::internal_error())?;
+                                    let game_history = game.mvc.model().game_history();
+                                    let mut fields = Vec::new();
+                                    for &(kind, text_id) in &[
+                                        (BoardSettingKind::Easy, resources::IDC_EDIT1),
+                                        (BoardSettingKind::Normal, resources::IDC_EDIT2),
+                                        (BoardSettingKind::Hard, resources::IDC_EDIT3),
+                                        (BoardSettingKind::Custom, resources::IDC_EDIT4),
+                                    ] {
+                                        let summary = game_history.summary(kind);
+                                        let text = if summary.played == 0 {
+                                            "No games played".to_owned()
+                                        } else {
+                                            format!(
+                                                "{} played, {:.0}% won, streak {} (best {}), \
+                                                 average {}, best {}",
+                                                summary.played,
+                                                summary.win_rate() * 100.0,
+                                                summary.current_streak,
+                                                summary.best_streak,
+                                                format_seconds(summary.average_time),
+                                                format_seconds(summary.best_time)
+                                            )
+                                        };
+                                        fields.push((text_id, text));
+                                    }
+                                    Ok(fields)
+                                })?;
+                                let (result, _) = ui_dialog(
+                                    window,
+                                    resources::IDD_STATISTICS,
+                                    fields,
+                                    &[resources::IDC_BUTTON1],
+                                )?;
+                                if result != resources::IDC_BUTTON1 {
+                                    break;
+                                }
+                                Ui::process_model_command(ModelCommand::ResetStatistics)?;
+                            },
+                            resources::IDM_FILE_STATISTICS_EXPORT => {
+                                if let Some(path) = Ui::call_save_file_dialog(window, 0, "csv") {
+                                    Ui::process_model_command(ModelCommand::ExportStatistics(
+                                        path,
+                                    ))?;
+                                }
+                            }
                             resources::IDM_FILE_EXIT => {
                                 window.destroy()?;
                             }
@@ -336,6 +731,51 @@ impl Ui {
                                     })?;
                                 }
                             }
+                            resources::IDM_ADVANCED_BOARD_CODE_EXPORT => {
+                                Ui::process_model_command(ModelCommand::ShowBoardCode)?;
+                            }
+                            resources::IDM_ADVANCED_EXPORT_IMAGE => {
+                                if let Some(path) = Ui::call_save_file_dialog(window, 0, "png") {
+                                    let zoom_ratio = THE_GAME.with(|game| {
+                                        let game =
+                                            game.try_borrow().or_else(|_| apiw::internal_error())?;
+                                        Ok(game.mvc.model().config().zoom_ratio)
+                                    })?;
+                                    Ui::process_model_command(ModelCommand::ExportImage(
+                                        path, zoom_ratio,
+                                    ))?;
+                                }
+                            }
+                            resources::IDM_ADVANCED_EXPORT_DRAWING
+                            | resources::IDM_ADVANCED_EXPORT_DRAWING_LABELED => {
+                                use crate::view_export::CoordinateLabels;
+
+                                if let Some(path) = Ui::call_save_file_dialog(window, 0, "svg") {
+                                    let labels = CoordinateLabels(
+                                        args.id() as isize
+                                            == resources::IDM_ADVANCED_EXPORT_DRAWING_LABELED,
+                                    );
+                                    Ui::process_model_command(ModelCommand::ExportDrawing(
+                                        path, labels,
+                                    ))?;
+                                }
+                            }
+                            resources::IDM_ADVANCED_BOARD_CODE_IMPORT => {
+                                let fields = vec![
+                                    (
+                                        resources::IDC_TEXT1,
+                                        "Enter the board code to play:".to_owned(),
+                                    ),
+                                    (resources::IDC_EDIT1, String::new()),
+                                ];
+                                if let Some(values) =
+                                    ui_edit_dialog(window, resources::IDD_BOARD_CODE, fields)?
+                                {
+                                    Ui::process_model_command(ModelCommand::LoadBoardCode(
+                                        values[1].clone(),
+                                    ))?;
+                                }
+                            }
                             resources::IDM_ADVANCED_RESTART => {
                                 THE_GAME.with(|game| {
                                     let mut game =
@@ -348,6 +788,111 @@ impl Ui {
                                     Ok(())
                                 })?;
                             }
+                            resources::IDM_ADVANCED_RECORD_RECORD => {
+                                if let Some(path) = Ui::call_save_file_dialog(window, 0, "cmr") {
+                                    ui_alert(
+                                        "Click OK or press Enter when you are ready to start recording.",
+                                    );
+                                    THE_GAME.with(|game| {
+                                        let mut game = game
+                                            .try_borrow_mut()
+                                            .or_else(|_| apiw::internal_error())?;
+                                        let game = &mut *game;
+
+                                        game.mvc.process_input(ControllerInput::ModelCommand(
+                                            ModelCommand::StartRecording(path),
+                                        ));
+                                        Ok(())
+                                    })?;
+                                }
+                            }
+                            resources::IDM_ADVANCED_RECORD_PLAY => {
+                                if let Some(path) = Ui::call_open_file_dialog(window, 0, "cmr") {
+                                    THE_GAME.with(|game| {
+                                        let mut game = game
+                                            .try_borrow_mut()
+                                            .or_else(|_| apiw::internal_error())?;
+                                        let game = &mut *game;
+
+                                        game.mvc.process_input(ControllerInput::ModelCommand(
+                                            ModelCommand::StartPlayback(path),
+                                        ));
+                                        Ok(())
+                                    })?;
+                                }
+                            }
+                            resources::IDM_ADVANCED_PLAYBACK_PAUSE => {
+                                Ui::process_model_command(ModelCommand::PlaybackTogglePause)?;
+                            }
+                            resources::IDM_ADVANCED_PLAYBACK_STEP_BACKWARD => {
+                                Ui::process_model_command(ModelCommand::PlaybackStepBackward)?;
+                            }
+                            resources::IDM_ADVANCED_PLAYBACK_STEP_FORWARD => {
+                                Ui::process_model_command(ModelCommand::PlaybackStepForward)?;
+                            }
+                            resources::IDM_ADVANCED_PLAYBACK_SLOWER => {
+                                Ui::process_model_command(ModelCommand::PlaybackSlower)?;
+                            }
+                            resources::IDM_ADVANCED_PLAYBACK_FASTER => {
+                                Ui::process_model_command(ModelCommand::PlaybackFaster)?;
+                            }
+                            resources::IDM_FILE_GAME_SEED => {
+                                let seed = THE_GAME.with(|game| {
+                                    let game =
+                                        game.try_borrow().or_else(|_| apiw::internal_error())?;
+                                    Ok(game.mvc.model().seed())
+                                })?;
+                                let fields = vec![(resources::IDC_EDIT1, seed.to_string())];
+                                if let Some(values) =
+                                    ui_edit_dialog(window, resources::IDD_GAME_SEED, fields)?
+                                {
+                                    if let Ok(seed) = values[0].trim().parse::<u64>() {
+                                        Ui::process_model_command(ModelCommand::NewGameWithSeed(
+                                            seed,
+                                        ))?;
+                                    } else {
+                                        ui_alert("Please enter a seed number.");
+                                    }
+                                }
+                            }
+                            resources::IDM_ADVANCED_PLAYBACK_JUMP => {
+                                let playback_position = THE_GAME.with(|game| {
+                                    let game =
+                                        game.try_borrow().or_else(|_| apiw::internal_error())?;
+                                    Ok(game.mvc.model().playback_position())
+                                })?;
+                                if let Some((position, len)) = playback_position {
+                                    let fields = vec![
+                                        (resources::IDC_TEXT1, format!("&Move (0 - {}):", len)),
+                                        (resources::IDC_EDIT1, position.to_string()),
+                                    ];
+                                    if let Some(values) = ui_edit_dialog(
+                                        window,
+                                        resources::IDD_PLAYBACK_JUMP,
+                                        fields,
+                                    )? {
+                                        if let Ok(idx) = values[1].trim().parse::<usize>() {
+                                            Ui::process_model_command(ModelCommand::PlaybackSeek(
+                                                idx,
+                                            ))?;
+                                        } else {
+                                            ui_alert("Please enter a move number.");
+                                        }
+                                    }
+                                }
+                            }
+                            resources::IDM_ADVANCED_RECORD_STOP => {
+                                THE_GAME.with(|game| {
+                                    let mut game =
+                                        game.try_borrow_mut().or_else(|_| apiw::internal_error())?;
+                                    let game = &mut *game;
+
+                                    game.mvc.process_input(ControllerInput::ModelCommand(
+                                        ModelCommand::StopRecordingOrPlayback,
+                                    ));
+                                    Ok(())
+                                })?;
+                            }
                             resources::IDM_HELP_ABOUT => {
                                 use apiw::windows_subsystem::dialog::DialogBuilder;
 
@@ -359,6 +904,23 @@ impl Ui {
                         Ok(())
                     },
                 )
+                .route_size(
+                    |_window: &ForeignWindow, size_args: SizeEventArgs| -> apiw::Result<()> {
+                        use crate::model::ModelCommand;
+                        use apiw::windows_subsystem::window::SizeEventArgType;
+
+                        match size_args.kind() {
+                            Some(SizeEventArgType::Minimized) => {
+                                Ui::process_model_command(ModelCommand::SetMinimized(true))?;
+                            }
+                            Some(SizeEventArgType::Restored) => {
+                                Ui::process_model_command(ModelCommand::SetMinimized(false))?;
+                            }
+                            _ => {}
+                        }
+                        Ok(())
+                    },
+                )
                 .route_destroy(|_window: &ForeignWindow| -> apiw::Result<()> {
                     use apiw::windows_subsystem::message::ForeignMessageLoop;
 
@@ -368,11 +930,12 @@ impl Ui {
                 });
         }
 
-        use apiw::windows_subsystem::window::TimerProcRequest;
+        fn main_window_timer_handler() {
+            use crate::model::ModelCommand;
 
-        fn main_window_timer_handler(request: TimerProcRequest) {
-            if let Some(window) = request.window() {
-                let _ = window.invalidate();
+            let _ = Ui::process_model_command(ModelCommand::Tick);
+            for command in DEFERRED_COMMANDS.with(|commands| commands.replace(Vec::new())) {
+                let _ = Ui::process_model_command(command);
             }
         }
 
@@ -400,13 +963,13 @@ impl Ui {
             .create()?;
 
         use std::num::NonZeroUsize;
-        use std::time::Duration;
 
+        TIMER_HANDLER.with(|handler| *handler.borrow_mut() = Some(main_window_timer_handler));
         window
             .set_timer_with_id(
                 NonZeroUsize::new(1).unwrap(),
                 Duration::from_millis(100),
-                timer_proc!(main_window_timer_handler),
+                timer_proc!(timer_handler),
             )?
             .show(apiw::shared::exe_cmd_show())?
             .update()?;
@@ -425,3 +988,107 @@ impl Ui {
         Ok(())
     }
 }
+
+impl UiBackend for Ui {
+    type Window = UiWindow;
+
+    fn create_main_window() -> UiResult<()> {
+        ui_result(Ui::create_main_window())
+    }
+
+    fn run_event_loop() -> UiResult<()> {
+        ui_result(Ui::run_event_loop())
+    }
+
+    fn invalidate(window: &UiWindow) -> UiResult<()> {
+        ui_result(window.invalidate())?;
+        Ok(())
+    }
+
+    fn set_client_size(window: &UiWindow, size: Size) -> UiResult<()> {
+        use apiw::graphics_subsystem::Rect;
+
+        let rect = Rect::new(apiw::graphics_subsystem::Point::ORIGIN, apiw_size(size));
+        let new_rect = ui_result(UiWindow::predict_window_rect_from_client_rect_and_window(
+            rect, window,
+        ))?;
+        ui_result(window.reposition_set_size(new_rect.size()))?;
+        ui_result(window.invalidate_and_erase())?;
+        Ok(())
+    }
+
+    fn set_captured(window: Option<&UiWindow>) -> UiResult<()> {
+        ui_result(UiWindow::set_captured(window))?;
+        Ok(())
+    }
+
+    fn set_timer(window: &UiWindow, interval: Duration, handler: fn()) -> UiResult<()> {
+        use std::num::NonZeroUsize;
+
+        TIMER_HANDLER.with(|timer_handler| *timer_handler.borrow_mut() = Some(handler));
+        ui_result(window.set_timer_with_id(
+            NonZeroUsize::new(1).unwrap(),
+            interval,
+            timer_proc!(timer_handler),
+        ))?;
+        Ok(())
+    }
+
+    fn set_menu_item_checked(window: &UiWindow, item_id: isize, checked: bool) -> UiResult<()> {
+        if let Some(mut menu) = ui_result(window.menu())? {
+            ui_result(menu.item_by_command(item_id as _).set_checked(checked))?;
+        }
+        Ok(())
+    }
+
+    fn set_menu_item_enabled(window: &UiWindow, item_id: isize, enabled: bool) -> UiResult<()> {
+        if let Some(mut menu) = ui_result(window.menu())? {
+            ui_result(menu.item_by_command(item_id as _).set_enabled(enabled))?;
+        }
+        Ok(())
+    }
+
+    fn alert(message: &str) {
+        ui_alert(message);
+    }
+
+    fn dialog(
+        parent: &UiWindow,
+        dialog_res_id: isize,
+        fields: Vec<(isize, String)>,
+        extra_buttons: &[isize],
+    ) -> UiResult<(isize, Vec<String>)> {
+        ui_result(ui_dialog(parent, dialog_res_id, fields, extra_buttons))
+    }
+
+    fn open_file_dialog(parent: &UiWindow, default_ext: &str) -> Option<PathBuf> {
+        Ui::call_open_file_dialog(parent, 0, default_ext)
+    }
+
+    fn save_file_dialog(parent: &UiWindow, default_ext: &str) -> Option<PathBuf> {
+        Ui::call_save_file_dialog(parent, 0, default_ext)
+    }
+
+    fn is_bitmap_pixel_opaque(
+        bitmap_id: isize,
+        point: Point,
+        transparent_key: RGBColor,
+    ) -> UiResult<bool> {
+        PIXEL_DCS.with(|pixel_dcs| {
+            let mut pixel_dcs = pixel_dcs.borrow_mut();
+            let idx = match pixel_dcs.iter().position(|(id, _)| *id == bitmap_id) {
+                Some(idx) => idx,
+                None => {
+                    let mut memdc =
+                        ui_result(UiLocalDC::new_compatible_memory_dc_for_current_screen())?;
+                    ui_result(memdc.set_background_color(apiw_color(transparent_key)))?;
+                    ui_result(memdc.select_bitmap(bitmap_instance(bitmap_id)))?;
+                    pixel_dcs.push((bitmap_id, memdc));
+                    pixel_dcs.len() - 1
+                }
+            };
+            let color = ui_result(pixel_dcs[idx].1.get_pixel(apiw_point(point)))?;
+            Ok(color.map_or(false, |color| color != apiw_color(transparent_key)))
+        })
+    }
+}
@@ -0,0 +1,270 @@
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Point {
+    x: isize,
+    y: isize,
+}
+
+impl Point {
+    pub const ORIGIN: Point = Point { x: 0, y: 0 };
+
+    pub const fn new(x: isize, y: isize) -> Self {
+        Point { x, y }
+    }
+
+    pub fn x(&self) -> isize {
+        self.x
+    }
+
+    pub fn y(&self) -> isize {
+        self.y
+    }
+
+    pub fn offset(&self, dx: isize, dy: isize) -> Point {
+        Point::new(self.x + dx, self.y + dy)
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Size {
+    cx: usize,
+    cy: usize,
+}
+
+impl Size {
+    pub const fn new(cx: usize, cy: usize) -> Self {
+        Size { cx, cy }
+    }
+
+    pub fn cx(&self) -> usize {
+        self.cx
+    }
+
+    pub fn cy(&self) -> usize {
+        self.cy
+    }
+}
+
+/// Like a GDI `RECT`, the right and bottom edges lie just outside the
+/// rectangle.
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub struct Rect {
+    left: isize,
+    top: isize,
+    right: isize,
+    bottom: isize,
+}
+
+impl Rect {
+    pub fn new(pos: Point, size: Size) -> Self {
+        Rect {
+            left: pos.x(),
+            top: pos.y(),
+            right: pos.x() + size.cx() as isize,
+            bottom: pos.y() + size.cy() as isize,
+        }
+    }
+
+    pub fn size(&self) -> Size {
+        Size::new(
+            (self.right - self.left).max(0) as usize,
+            (self.bottom - self.top).max(0) as usize,
+        )
+    }
+
+    pub fn top_left(&self) -> Point {
+        Point::new(self.left, self.top)
+    }
+
+    pub fn top_right(&self) -> Point {
+        Point::new(self.right, self.top)
+    }
+
+    pub fn bottom_left(&self) -> Point {
+        Point::new(self.left, self.bottom)
+    }
+
+    pub fn bottom_right(&self) -> Point {
+        Point::new(self.right, self.bottom)
+    }
+
+    pub fn deflate(&self, v: usize) -> Rect {
+        let v = v as isize;
+        Rect {
+            left: self.left + v,
+            top: self.top + v,
+            right: self.right - v,
+            bottom: self.bottom - v,
+        }
+    }
+
+    pub fn contains(&self, point: Point) -> bool {
+        self.left <= point.x()
+            && point.x() < self.right
+            && self.top <= point.y()
+            && point.y() < self.bottom
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+pub struct RGBColor {
+    r: u8,
+    g: u8,
+    b: u8,
+}
+
+impl RGBColor {
+    pub const BLACK: RGBColor = RGBColor::new(0, 0, 0);
+    pub const GRAY: RGBColor = RGBColor::new(128, 128, 128);
+    pub const SILVER: RGBColor = RGBColor::new(192, 192, 192);
+    pub const WHITE: RGBColor = RGBColor::new(255, 255, 255);
+    pub const FUCHSIA: RGBColor = RGBColor::new(255, 0, 255);
+
+    pub const fn new(r: u8, g: u8, b: u8) -> Self {
+        RGBColor { r, g, b }
+    }
+
+    pub fn r(&self) -> u8 {
+        self.r
+    }
+
+    pub fn g(&self) -> u8 {
+        self.g
+    }
+
+    pub fn b(&self) -> u8 {
+        self.b
+    }
+}
+
+/// Failure reported by a UI backend, kept as text since every backend has
+/// its own error type.
+#[derive(Debug)]
+pub struct UiError(String);
+
+impl UiError {
+    pub fn new<E: fmt::Debug>(error: E) -> Self {
+        UiError(format!("{:?}", error))
+    }
+
+    pub fn with_message(message: impl Into<String>) -> Self {
+        UiError(message.into())
+    }
+}
+
+impl fmt::Display for UiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+pub type UiResult<T> = Result<T, UiError>;
+
+/// Painting primitives a backend provides for the game view. Coordinates
+/// are in unzoomed client pixels; bitmaps are named by their resource id
+/// (`IDB_*`).
+pub trait UiCanvas {
+    /// Scales everything painted afterwards by `ratio`.
+    fn set_zoom(&mut self, ratio: usize) -> UiResult<()>;
+
+    /// Connects `points` with straight lines of the given pen width.
+    fn draw_polyline(&mut self, points: &[Point], width: usize, color: RGBColor) -> UiResult<()>;
+
+    /// Copies the `size` area at `src_pos` of a bitmap to `dest_pos`,
+    /// leaving out the pixels of `transparent_key` if there is one.
+    fn draw_sprite(
+        &mut self,
+        bitmap_id: isize,
+        src_pos: Point,
+        size: Size,
+        dest_pos: Point,
+        transparent_key: Option<RGBColor>,
+    ) -> UiResult<()>;
+}
+
+pub trait UiDraw {
+    fn draw(self, canvas: &mut dyn UiCanvas) -> UiResult<()>;
+}
+
+impl<'a> dyn UiCanvas + 'a {
+    pub fn draw<T: UiDraw>(&mut self, item: T) -> UiResult<&mut Self> {
+        item.draw(self)?;
+        Ok(self)
+    }
+
+    pub fn draw_from_iter<T: UiDraw, I: IntoIterator<Item = T>>(
+        &mut self,
+        items: I,
+    ) -> UiResult<&mut Self> {
+        for item in items {
+            item.draw(self)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Everything the game needs from a windowing system besides painting.
+pub trait UiBackend {
+    type Window: Clone;
+
+    /// Creates the main window, which routes its input to the game.
+    fn create_main_window() -> UiResult<()>;
+
+    fn run_event_loop() -> UiResult<()>;
+
+    fn invalidate(window: &Self::Window) -> UiResult<()>;
+
+    /// Resizes the window so that its client area has `size`.
+    fn set_client_size(window: &Self::Window, size: Size) -> UiResult<()>;
+
+    /// Keeps sending mouse input to `window` while a button is held outside
+    /// of it; `None` releases it.
+    fn set_captured(window: Option<&Self::Window>) -> UiResult<()>;
+
+    /// Calls `handler` every `interval` while the window lives.
+    fn set_timer(window: &Self::Window, interval: Duration, handler: fn()) -> UiResult<()>;
+
+    fn set_menu_item_checked(window: &Self::Window, item_id: isize, checked: bool) -> UiResult<()>;
+
+    fn set_menu_item_enabled(window: &Self::Window, item_id: isize, enabled: bool) -> UiResult<()>;
+
+    fn alert(message: &str);
+
+    /// Shows a dialog resource with the given controls prefilled. Returns the
+    /// id of the button that closed it along with the final control texts.
+    fn dialog(
+        parent: &Self::Window,
+        dialog_res_id: isize,
+        fields: Vec<(isize, String)>,
+        extra_buttons: &[isize],
+    ) -> UiResult<(isize, Vec<String>)>;
+
+    /// Like `dialog`, but only returns the texts when confirmed with OK.
+    fn edit_dialog(
+        parent: &Self::Window,
+        dialog_res_id: isize,
+        fields: Vec<(isize, String)>,
+    ) -> UiResult<Option<Vec<String>>> {
+        use crate::view_assets::resources::IDOK;
+
+        let (result, values) = Self::dialog(parent, dialog_res_id, fields, &[])?;
+        if result != IDOK {
+            return Ok(None);
+        }
+        Ok(Some(values))
+    }
+
+    fn open_file_dialog(parent: &Self::Window, default_ext: &str) -> Option<PathBuf>;
+
+    fn save_file_dialog(parent: &Self::Window, default_ext: &str) -> Option<PathBuf>;
+
+    /// Whether the bitmap pixel at `point` differs from `transparent_key`.
+    fn is_bitmap_pixel_opaque(
+        bitmap_id: isize,
+        point: Point,
+        transparent_key: RGBColor,
+    ) -> UiResult<bool>;
+}
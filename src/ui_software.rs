@@ -0,0 +1,375 @@
+use crate::model::Model;
+use crate::ui_backend::{Point, RGBColor, Size, UiCanvas, UiError, UiResult};
+use crate::view_assets::resources::*;
+use crate::view_assets::Assets;
+use crate::view_layout::ViewLayout;
+
+/// Returns the encoded bitmap resource, the same files the Windows resource
+/// script embeds.
+fn resource_data(bitmap_id: isize) -> Option<&'static [u8]> {
+    match bitmap_id {
+        IDB_BLOCKS => Some(include_bytes!("../res/Blocks.bmp")),
+        IDB_BUTTON => Some(include_bytes!("../res/Button.bmp")),
+        IDB_DIGIT => Some(include_bytes!("../res/Digit.bmp")),
+        _ => None,
+    }
+}
+
+pub struct SoftwareBitmap {
+    size: Size,
+    pixels: Vec<RGBColor>,
+}
+
+impl SoftwareBitmap {
+    pub fn from_resource_id(bitmap_id: isize) -> UiResult<Self> {
+        let data = resource_data(bitmap_id)
+            .ok_or_else(|| UiError::with_message(format!("unknown bitmap {}", bitmap_id)))?;
+        Self::decode_bmp(data)
+    }
+
+    /// Decodes an uncompressed Windows bitmap with 1, 4, 8, 24 or 32 bits per
+    /// pixel.
+    pub fn decode_bmp(data: &[u8]) -> UiResult<Self> {
+        fn read_u16(data: &[u8], offset: usize) -> UiResult<u16> {
+            data.get(offset..offset + 2)
+                .map(|v| u16::from_le_bytes([v[0], v[1]]))
+                .ok_or_else(|| UiError::with_message("truncated bitmap"))
+        }
+        fn read_u32(data: &[u8], offset: usize) -> UiResult<u32> {
+            data.get(offset..offset + 4)
+                .map(|v| u32::from_le_bytes([v[0], v[1], v[2], v[3]]))
+                .ok_or_else(|| UiError::with_message("truncated bitmap"))
+        }
+
+        const FILE_HEADER_SIZE: usize = 14;
+        const BI_RGB: u32 = 0;
+
+        if data.get(0..2) != Some(b"BM") {
+            return Err(UiError::with_message("not a bitmap"));
+        }
+        let pixel_offset = read_u32(data, 10)? as usize;
+        let info_size = read_u32(data, FILE_HEADER_SIZE)? as usize;
+        let width = read_u32(data, FILE_HEADER_SIZE + 4)? as i32;
+        let height = read_u32(data, FILE_HEADER_SIZE + 8)? as i32;
+        let bit_count = read_u16(data, FILE_HEADER_SIZE + 14)?;
+        let compression = read_u32(data, FILE_HEADER_SIZE + 16)?;
+        let colors_used = read_u32(data, FILE_HEADER_SIZE + 32)? as usize;
+        if width <= 0 || height == 0 || compression != BI_RGB {
+            return Err(UiError::with_message("unsupported bitmap"));
+        }
+
+        let palette = match bit_count {
+            1 | 4 | 8 => {
+                let count = if colors_used != 0 {
+                    colors_used
+                } else {
+                    1 << bit_count
+                };
+                let start = FILE_HEADER_SIZE + info_size;
+                let entries = data
+                    .get(start..start + count * 4)
+                    .ok_or_else(|| UiError::with_message("truncated bitmap"))?;
+                entries
+                    .chunks(4)
+                    .map(|v| RGBColor::new(v[2], v[1], v[0]))
+                    .collect()
+            }
+            24 | 32 => Vec::new(),
+            _ => return Err(UiError::with_message("unsupported bitmap")),
+        };
+
+        let (width, top_down, height) =
+            (width as usize, height < 0, (height as i64).abs() as usize);
+        let stride = (width * bit_count as usize + 31) / 32 * 4;
+        let mut pixels = Vec::with_capacity(width * height);
+        for y in 0..height {
+            let row_idx = if top_down { y } else { height - 1 - y };
+            let start = pixel_offset + row_idx * stride;
+            let row = data
+                .get(start..start + stride)
+                .ok_or_else(|| UiError::with_message("truncated bitmap"))?;
+            for x in 0..width {
+                let color = match bit_count {
+                    24 | 32 => {
+                        let v = &row[x * bit_count as usize / 8..];
+                        RGBColor::new(v[2], v[1], v[0])
+                    }
+                    _ => {
+                        let bit = x * bit_count as usize;
+                        let shift = 8 - bit_count as usize - bit % 8;
+                        let idx = (row[bit / 8] >> shift) as usize & ((1 << bit_count) - 1);
+                        *palette
+                            .get(idx)
+                            .ok_or_else(|| UiError::with_message("bad palette index"))?
+                    }
+                };
+                pixels.push(color);
+            }
+        }
+
+        Ok(SoftwareBitmap {
+            size: Size::new(width, height),
+            pixels,
+        })
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    pub fn pixel(&self, point: Point) -> Option<RGBColor> {
+        let (x, y) = (point.x(), point.y());
+        if x < 0 || y < 0 || x as usize >= self.size.cx() || y as usize >= self.size.cy() {
+            return None;
+        }
+        Some(self.pixels[y as usize * self.size.cx() + x as usize])
+    }
+}
+
+/// Paints into an in-memory RGBA framebuffer, without any windowing system.
+/// It starts out filled with the button face color the game window uses as
+/// its background.
+pub struct SoftwareCanvas {
+    size: Size,
+    rgba: Vec<u8>,
+    zoom: usize,
+    bitmaps: Vec<(isize, SoftwareBitmap)>,
+}
+
+impl SoftwareCanvas {
+    pub const BACKGROUND_COLOR: RGBColor = RGBColor::SILVER;
+
+    pub fn new(size: Size) -> Self {
+        let background = Self::BACKGROUND_COLOR;
+        let rgba = [background.r(), background.g(), background.b(), 0xFF]
+            .iter()
+            .copied()
+            .cycle()
+            .take(size.cx() * size.cy() * 4)
+            .collect();
+        SoftwareCanvas {
+            size,
+            rgba,
+            zoom: 1,
+            bitmaps: Vec::new(),
+        }
+    }
+
+    /// Renders the whole game area of `model` at the zoom ratio of `layout`.
+    pub fn render(layout: &ViewLayout, model: &Model, assets: &Assets) -> UiResult<Self> {
+        let mut canvas = SoftwareCanvas::new(layout.client_size());
+        layout.draw(&mut canvas, model, assets)?;
+        Ok(canvas)
+    }
+
+    pub fn size(&self) -> Size {
+        self.size
+    }
+
+    /// Rows of pixels from top to bottom, four bytes per pixel.
+    pub fn rgba(&self) -> &[u8] {
+        &self.rgba
+    }
+
+    pub fn into_rgba(self) -> Vec<u8> {
+        self.rgba
+    }
+
+    pub fn pixel(&self, point: Point) -> Option<RGBColor> {
+        let offset = self.pixel_offset(point.x(), point.y())?;
+        let v = &self.rgba[offset..offset + 4];
+        Some(RGBColor::new(v[0], v[1], v[2]))
+    }
+
+    fn pixel_offset(&self, x: isize, y: isize) -> Option<usize> {
+        if x < 0 || y < 0 || x as usize >= self.size.cx() || y as usize >= self.size.cy() {
+            return None;
+        }
+        Some((y as usize * self.size.cx() + x as usize) * 4)
+    }
+
+    fn set_pixel(&mut self, x: isize, y: isize, color: RGBColor) {
+        if let Some(offset) = self.pixel_offset(x, y) {
+            self.rgba[offset..offset + 4].copy_from_slice(&[color.r(), color.g(), color.b(), 0xFF]);
+        }
+    }
+
+    fn fill_square(&mut self, x: isize, y: isize, side: usize, color: RGBColor) {
+        for dy in 0..side as isize {
+            for dx in 0..side as isize {
+                self.set_pixel(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    fn bitmap(&mut self, bitmap_id: isize) -> UiResult<&SoftwareBitmap> {
+        let idx = match self.bitmaps.iter().position(|(id, _)| *id == bitmap_id) {
+            Some(idx) => idx,
+            None => {
+                let bitmap = SoftwareBitmap::from_resource_id(bitmap_id)?;
+                self.bitmaps.push((bitmap_id, bitmap));
+                self.bitmaps.len() - 1
+            }
+        };
+        Ok(&self.bitmaps[idx].1)
+    }
+}
+
+impl UiCanvas for SoftwareCanvas {
+    fn set_zoom(&mut self, ratio: usize) -> UiResult<()> {
+        self.zoom = ratio.max(1);
+        Ok(())
+    }
+
+    fn draw_polyline(&mut self, points: &[Point], width: usize, color: RGBColor) -> UiResult<()> {
+        // like GDI, the last point itself is left out. Wide pens are centered
+        // on the line, leaning to the top left when the width is even.
+        let zoom = self.zoom as isize;
+        let width = width.max(1);
+        let lean = width as isize / 2;
+        for segment in points.windows(2) {
+            let (x0, y0) = (segment[0].x(), segment[0].y());
+            let (x1, y1) = (segment[1].x(), segment[1].y());
+            let (dx, dy) = ((x1 - x0).abs(), -(y1 - y0).abs());
+            let (sx, sy) = ((x1 - x0).signum(), (y1 - y0).signum());
+            let (mut x, mut y, mut err) = (x0, y0, dx + dy);
+            while (x, y) != (x1, y1) {
+                self.fill_square(
+                    (x - lean) * zoom,
+                    (y - lean) * zoom,
+                    width * self.zoom,
+                    color,
+                );
+                let e2 = 2 * err;
+                if e2 >= dy {
+                    err += dy;
+                    x += sx;
+                }
+                if e2 <= dx {
+                    err += dx;
+                    y += sy;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn draw_sprite(
+        &mut self,
+        bitmap_id: isize,
+        src_pos: Point,
+        size: Size,
+        dest_pos: Point,
+        transparent_key: Option<RGBColor>,
+    ) -> UiResult<()> {
+        let zoom = self.zoom;
+        let mut spans = Vec::with_capacity(size.cx() * size.cy());
+        let bitmap = self.bitmap(bitmap_id)?;
+        for y in 0..size.cy() as isize {
+            for x in 0..size.cx() as isize {
+                let color = match bitmap.pixel(src_pos.offset(x, y)) {
+                    Some(color) => color,
+                    None => continue,
+                };
+                if Some(color) == transparent_key {
+                    continue;
+                }
+                spans.push((x, y, color));
+            }
+        }
+        for (x, y, color) in spans {
+            self.fill_square(
+                (dest_pos.x() + x) * zoom as isize,
+                (dest_pos.y() + y) * zoom as isize,
+                zoom,
+                color,
+            );
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model_storage::ScratchDir;
+    use crate::view_assets::BlockSpriteSheet;
+
+    #[test]
+    fn resource_bitmaps_decode() {
+        for &(bitmap_id, cx, cy) in &[
+            (IDB_BLOCKS, 48, 400),
+            (IDB_BUTTON, 24, 120),
+            (IDB_DIGIT, 13, 276),
+        ] {
+            let bitmap = SoftwareBitmap::from_resource_id(bitmap_id).unwrap();
+            assert_eq!(bitmap.size(), Size::new(cx, cy));
+        }
+        let blocks = SoftwareBitmap::from_resource_id(IDB_BLOCKS).unwrap();
+        assert_eq!(
+            blocks.pixel(Point::ORIGIN),
+            Some(BlockSpriteSheet::TRANSPARENT_COLOR)
+        );
+        assert!(SoftwareBitmap::decode_bmp(b"BM").is_err());
+    }
+
+    #[test]
+    fn transparent_pixels_are_skipped() {
+        let size = Size::new(BlockSpriteSheet::BLOCKSIZE_X, BlockSpriteSheet::BLOCKSIZE_Y);
+        let mut canvas = SoftwareCanvas::new(size);
+        canvas
+            .draw_sprite(
+                IDB_BLOCKS,
+                Point::ORIGIN,
+                size,
+                Point::ORIGIN,
+                Some(BlockSpriteSheet::TRANSPARENT_COLOR),
+            )
+            .unwrap();
+        assert_eq!(canvas.pixel(Point::ORIGIN), Some(RGBColor::SILVER));
+        let blocks = SoftwareBitmap::from_resource_id(IDB_BLOCKS).unwrap();
+        let middle = Point::new(size.cx() as isize / 2, size.cy() as isize - 2);
+        assert_eq!(canvas.pixel(middle), blocks.pixel(middle));
+    }
+
+    #[test]
+    fn whole_view_renders_headless() {
+        // nothing is stored in the directory, so the model has the default config.
+        let dir = ScratchDir::new("render");
+        let model = Model::with_storage(dir.storage());
+        let mut layout = ViewLayout::new(model.size());
+        let canvas = SoftwareCanvas::render(&layout, &model, &Assets::new()).unwrap();
+        assert_eq!(canvas.size(), layout.client_size());
+        assert_eq!(canvas.pixel(Point::ORIGIN), Some(RGBColor::WHITE));
+        assert!(canvas
+            .rgba()
+            .chunks(4)
+            .all(|v| v[..3] != [0xFF, 0x00, 0xFF][..]));
+
+        layout.update_zoom_ratio(crate::model_config::ZoomRatio::Zoom3x);
+        let zoomed = SoftwareCanvas::render(&layout, &model, &Assets::new()).unwrap();
+        let size = canvas.size();
+        assert_eq!(zoomed.size(), Size::new(size.cx() * 3, size.cy() * 3));
+        for y in 0..size.cy() as isize {
+            for x in 0..size.cx() as isize {
+                let pixel = canvas.pixel(Point::new(x, y));
+                assert_eq!(zoomed.pixel(Point::new(x * 3 + 2, y * 3 + 1)), pixel);
+            }
+        }
+    }
+
+    #[test]
+    fn zoom_scales_lines_and_sprites() {
+        let mut canvas = SoftwareCanvas::new(Size::new(20, 20));
+        canvas.set_zoom(2).unwrap();
+        canvas
+            .draw_polyline(&[Point::new(1, 1), Point::new(5, 1)], 1, RGBColor::BLACK)
+            .unwrap();
+        for x in 2..10 {
+            assert_eq!(canvas.pixel(Point::new(x, 2)), Some(RGBColor::BLACK));
+            assert_eq!(canvas.pixel(Point::new(x, 3)), Some(RGBColor::BLACK));
+        }
+        assert_eq!(canvas.pixel(Point::new(10, 2)), Some(RGBColor::SILVER));
+        assert_eq!(canvas.pixel(Point::new(2, 4)), Some(RGBColor::SILVER));
+    }
+}
@@ -1,73 +1,12 @@
-use crate::model::Block;
+use crate::model::BlockDisplayKind;
 use crate::model::BlockShape;
-use apiw;
-use apiw::graphics_subsystem::device_context::{LocalDeviceContext, ScopedDeviceContext};
-use apiw::graphics_subsystem::object::Bitmap;
-use apiw::graphics_subsystem::RGBColor;
-use apiw::graphics_subsystem::TenaryROP;
-use apiw::graphics_subsystem::{Point, Size};
-use apiw::shared::ManagedStrategy;
-use smallvec::{smallvec, SmallVec};
-use std::borrow::BorrowMut;
-use std::cell::RefCell;
-use std::marker::PhantomData;
+use crate::model::GameButtonDisplayKind;
+use crate::ui_backend::{Point, RGBColor, Size, UiBackend, UiCanvas, UiResult};
 
 #[path = "view_assets_catalog.rs"]
-pub(crate) mod resources;
+pub mod resources;
 
 use self::resources::*;
-use crate::model::BlockDisplayKind;
-use crate::model::Board;
-use crate::model::GameButtonDisplayKind;
-
-pub struct SpriteSheetInstance<
-    'a,
-    'd,
-    S: BorrowMut<LocalDeviceContext>,
-    D: BorrowMut<ScopedDeviceContext<'d>>,
-> {
-    sprite_sheet: &'a dyn SpriteSheet,
-    memory_dc: S,
-    target_dc: D,
-    sprite_bitmap: Option<Bitmap>,
-    phantom: PhantomData<&'d ()>,
-}
-
-impl<'a, 'd, S, D> SpriteSheetInstance<'a, 'd, S, D>
-where
-    S: BorrowMut<LocalDeviceContext>,
-    D: BorrowMut<ScopedDeviceContext<'d>>,
-{
-    #[allow(unreachable_patterns)]
-    pub fn draw_sprite(&mut self, dest_pos: Point, idx: usize) -> apiw::Result<()> {
-        let src_dc = self.memory_dc.borrow();
-        let dest_dc = self.target_dc.borrow_mut();
-        let (src_pos, size) = self.sprite_sheet.sprite_coord(idx);
-        match self.sprite_sheet.sprite_draw_param() {
-            SpriteDrawParam::TransparentBlt(key) => {
-                dest_dc.transparentblt(src_dc, src_pos, size, dest_pos, size, key)?
-            }
-            SpriteDrawParam::BitBlt | _ => {
-                dest_dc.bitblt(src_dc, src_pos, dest_pos, size, TenaryROP::SRCCOPY)?
-            }
-        };
-        Ok(())
-    }
-}
-
-impl<'a, 'd, S, D> Drop for SpriteSheetInstance<'a, 'd, S, D>
-where
-    S: BorrowMut<LocalDeviceContext>,
-    D: BorrowMut<ScopedDeviceContext<'d>>,
-{
-    fn drop(&mut self) {
-        self.memory_dc.borrow_mut().reset_to_initial_state();
-
-        if let Some(sprite_bitmap) = self.sprite_bitmap.take() {
-            self.sprite_sheet.reuse_bitmap_instance(sprite_bitmap);
-        }
-    }
-}
 
 pub enum SpriteDrawParam {
     BitBlt,
@@ -81,45 +20,26 @@ pub trait SpriteSheet {
         SpriteDrawParam::BitBlt
     }
 
-    fn bitmap_instance(&self) -> Bitmap;
-
-    fn reuse_bitmap_instance(&self, clean_instance: Bitmap);
-
-    fn instantiate<'a, 'b, 'c>(
-        &'a self,
-        target_dc: &'b mut ScopedDeviceContext<'c>,
-    ) -> apiw::Result<
-        SpriteSheetInstance<'a, 'c, LocalDeviceContext, &'b mut ScopedDeviceContext<'c>>,
-    >
-    where
-        Self: Sized,
-    {
-        let mut memory_dc = LocalDeviceContext::new_compatible_memory_dc(target_dc)?;
-        let bitmap = self.bitmap_instance();
-        memory_dc.select_bitmap(bitmap.clone())?;
-        let sprite_sheet = self as _;
-        Ok(SpriteSheetInstance {
-            sprite_sheet,
-            memory_dc,
-            target_dc,
-            sprite_bitmap: Some(bitmap),
-            phantom: PhantomData,
-        })
+    fn bitmap_id(&self) -> isize;
+
+    fn draw_sprite(&self, canvas: &mut dyn UiCanvas, dest_pos: Point, idx: usize) -> UiResult<()> {
+        let (src_pos, size) = self.sprite_coord(idx);
+        let transparent_key = match self.sprite_draw_param() {
+            SpriteDrawParam::TransparentBlt(key) => Some(key),
+            SpriteDrawParam::BitBlt => None,
+        };
+        canvas.draw_sprite(self.bitmap_id(), src_pos, size, dest_pos, transparent_key)
     }
 }
 
-pub struct DigitSpriteSheet {
-    instances: RefCell<SmallVec<[Bitmap; 1]>>,
-}
+pub struct DigitSpriteSheet;
 
 impl DigitSpriteSheet {
     pub const DIGIT_WIDTH: usize = 13;
     pub const DIGIT_HEIGHT: usize = 23;
 
     pub fn new() -> Self {
-        DigitSpriteSheet {
-            instances: RefCell::new(smallvec![]),
-        }
+        DigitSpriteSheet
     }
 
     pub fn sprite_index_neg() -> usize {
@@ -140,31 +60,19 @@ impl SpriteSheet for DigitSpriteSheet {
         )
     }
 
-    fn bitmap_instance(&self) -> Bitmap {
-        if let Some(v) = self.instances.borrow_mut().pop() {
-            v
-        } else {
-            Bitmap::load_from_resource_id(IDB_DIGIT as _).expect("Failed to load resource bitmap.")
-        }
-    }
-
-    fn reuse_bitmap_instance(&self, v: Bitmap) {
-        self.instances.borrow_mut().push(v)
+    fn bitmap_id(&self) -> isize {
+        IDB_DIGIT
     }
 }
 
-pub struct GameButtonSpriteSheet {
-    instances: RefCell<SmallVec<[Bitmap; 1]>>,
-}
+pub struct GameButtonSpriteSheet;
 
 impl GameButtonSpriteSheet {
     pub const BUTTON_WIDTH: usize = 24;
     pub const BUTTON_HEIGHT: usize = 24;
 
     pub fn new() -> Self {
-        GameButtonSpriteSheet {
-            instances: RefCell::new(smallvec![]),
-        }
+        GameButtonSpriteSheet
     }
 
     pub fn sprite_index(state: GameButtonDisplayKind) -> usize {
@@ -186,22 +94,12 @@ impl SpriteSheet for GameButtonSpriteSheet {
         )
     }
 
-    fn bitmap_instance(&self) -> Bitmap {
-        if let Some(v) = self.instances.borrow_mut().pop() {
-            v
-        } else {
-            Bitmap::load_from_resource_id(IDB_BUTTON as _).expect("Failed to load resource bitmap.")
-        }
-    }
-
-    fn reuse_bitmap_instance(&self, v: Bitmap) {
-        self.instances.borrow_mut().push(v)
+    fn bitmap_id(&self) -> isize {
+        IDB_BUTTON
     }
 }
 
-pub struct BlockSpriteSheet {
-    instances: RefCell<SmallVec<[Bitmap; 1]>>,
-}
+pub struct BlockSpriteSheet;
 
 impl BlockSpriteSheet {
     pub const BLOCKSIZE_X: usize = 24;
@@ -213,9 +111,7 @@ impl BlockSpriteSheet {
     pub const TRANSPARENT_COLOR: RGBColor = RGBColor::FUCHSIA;
 
     pub fn new() -> Self {
-        BlockSpriteSheet {
-            instances: RefCell::new(smallvec![]),
-        }
+        BlockSpriteSheet
     }
 
     pub fn calc_block_area_size(board_size: (usize, usize)) -> Size {
@@ -225,41 +121,19 @@ impl BlockSpriteSheet {
         )
     }
 
-    pub fn hit_test_shape(shape: BlockShape, pos: (usize, usize)) -> apiw::Result<bool> {
-        thread_local! {
-            static SHAPE_DC: RefCell<LocalDeviceContext> = {
-                let mut memdc = LocalDeviceContext::new_compatible_memory_dc_for_current_screen()
-                    .expect("Failed to create memory dc.");
-                let bitmap = Bitmap::load_from_resource_id(IDB_BLOCKS as _)
-                    .expect("Failed to load resource bitmap.");
-                memdc.set_background_color(BlockSpriteSheet::TRANSPARENT_COLOR)
-                    .expect("Failed to select background color.");
-                memdc.select_bitmap(bitmap)
-                    .expect("Failed to select bitmap.");
-                RefCell::new(memdc)
-            };
-        }
+    /// Whether `pos`, relative to the top left of a block, lies inside its
+    /// triangle.
+    pub fn hit_test_shape<B: UiBackend>(shape: BlockShape, pos: (usize, usize)) -> UiResult<bool> {
+        let shape_offset = match shape {
+            BlockShape::DeltaLike => (0isize, 0isize),
+            BlockShape::RevDeltaLike => (0isize, Self::BLOCKSIZE_X as isize),
+        };
 
-        SHAPE_DC.with(|shape_dc| {
-            let mut shape_dc = shape_dc.borrow_mut();
-
-            let shape_offset = match shape {
-                BlockShape::DeltaLike => (0isize, 0isize),
-                BlockShape::RevDeltaLike => (0isize, Self::BLOCKSIZE_X as isize),
-            };
-
-            let point = Point::new(
-                pos.1 as isize + shape_offset.1,
-                pos.0 as isize + shape_offset.0,
-            );
-            if let Some(color) = shape_dc.get_pixel(point)? {
-                if color != Self::TRANSPARENT_COLOR {
-                    return Ok(true);
-                }
-            }
-
-            Ok(false)
-        })
+        let point = Point::new(
+            pos.1 as isize + shape_offset.1,
+            pos.0 as isize + shape_offset.0,
+        );
+        B::is_bitmap_pixel_opaque(IDB_BLOCKS, point, Self::TRANSPARENT_COLOR)
     }
 
     pub fn calc_block_pos(minefield_pos: Point, board_item: (usize, usize)) -> Point {
@@ -306,16 +180,8 @@ impl SpriteSheet for BlockSpriteSheet {
         SpriteDrawParam::TransparentBlt(Self::TRANSPARENT_COLOR)
     }
 
-    fn bitmap_instance(&self) -> Bitmap {
-        if let Some(v) = self.instances.borrow_mut().pop() {
-            v
-        } else {
-            Bitmap::load_from_resource_id(IDB_BLOCKS as _).expect("Failed to load resource bitmap.")
-        }
-    }
-
-    fn reuse_bitmap_instance(&self, v: Bitmap) {
-        self.instances.borrow_mut().push(v)
+    fn bitmap_id(&self) -> isize {
+        IDB_BLOCKS
     }
 }
 
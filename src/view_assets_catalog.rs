@@ -1,6 +1,8 @@
 #![allow(dead_code)]
 #![allow(non_upper_case_globals)]
 
+pub const IDOK: isize = 1;
+pub const IDCANCEL: isize = 2;
 pub const IDC_MYICON: isize = 2;
 pub const IDD_CHARLESMINE_DIALOG: isize = 102;
 pub const IDS_APP_TITLE: isize = 103;
@@ -31,20 +33,45 @@ pub const IDM_FILE_GAME_CUSTOM: isize = 155;
 pub const IDM_FILE_MARK: isize = 156;
 pub const IDM_FILE_HERO_LIST: isize = 157;
 pub const IDM_FILE_EXIT: isize = 158;
+pub const IDM_FILE_GAME_SEED: isize = 159;
+pub const IDM_FILE_NO_GUESS: isize = 160;
 pub const IDM_ADVANCED_LOADMAP: isize = 161;
 pub const IDM_ADVANCED_SAVEMAP: isize = 162;
 pub const IDM_ADVANCED_RESTART: isize = 163;
 pub const IDM_ADVANCED_RECORD_RECORD: isize = 164;
 pub const IDM_ADVANCED_RECORD_PLAY: isize = 166;
 pub const IDM_ADVANCED_RECORD_STOP: isize = 167;
+pub const IDM_ADVANCED_BOARD_CODE_EXPORT: isize = 168;
+pub const IDM_ADVANCED_BOARD_CODE_IMPORT: isize = 169;
 pub const IDM_ADVANCED_ZOOM_1x: isize = 170;
 pub const IDM_ADVANCED_ZOOM_2x: isize = 171;
 pub const IDM_ADVANCED_ZOOM_3x: isize = 172;
+pub const IDM_ADVANCED_PLAYBACK_PAUSE: isize = 173;
+pub const IDM_ADVANCED_PLAYBACK_STEP_BACKWARD: isize = 174;
+pub const IDM_ADVANCED_PLAYBACK_STEP_FORWARD: isize = 175;
+pub const IDM_ADVANCED_PLAYBACK_JUMP: isize = 176;
+pub const IDM_ADVANCED_PLAYBACK_SLOWER: isize = 177;
+pub const IDM_ADVANCED_PLAYBACK_FASTER: isize = 178;
+pub const IDM_ADVANCED_HEATMAP: isize = 179;
+pub const IDM_ADVANCED_HINT: isize = 180;
+pub const IDM_ADVANCED_UNDO: isize = 181;
+pub const IDM_ADVANCED_REDO: isize = 182;
+pub const IDM_FILE_SAFE_START: isize = 183;
+pub const IDM_FILE_STATISTICS: isize = 184;
+pub const IDM_FILE_STATISTICS_EXPORT: isize = 185;
+pub const IDM_FILE_PAUSE: isize = 186;
+pub const IDM_ADVANCED_EXPORT_IMAGE: isize = 187;
+pub const IDM_ADVANCED_EXPORT_DRAWING: isize = 188;
+pub const IDM_ADVANCED_EXPORT_DRAWING_LABELED: isize = 189;
 pub const IDM_HELP_ABOUT: isize = 199;
 pub const IDD_ABOUTBOX: isize = 201;
 pub const IDD_CUSTOM_GAME: isize = 202;
 pub const IDD_HERO_NAME: isize = 203;
 pub const IDD_HERO_LIST: isize = 204;
+pub const IDD_PLAYBACK_JUMP: isize = 205;
+pub const IDD_GAME_SEED: isize = 206;
+pub const IDD_BOARD_CODE: isize = 207;
+pub const IDD_STATISTICS: isize = 208;
 pub const IDS_ABOUTTEXT: isize = 241;
 pub const IDS_ABOUTTEXT1: isize = 242;
 pub const IDS_ABOUTTEXT2: isize = 243;
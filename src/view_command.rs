@@ -0,0 +1,104 @@
+use crate::model_config::{self, BoardSettingKind};
+use crate::model_gamemode;
+use crate::model_stats;
+use std::fmt;
+
+#[derive(Debug)]
+pub enum AlertFailure {
+    FileIOError,
+    FileFormatError,
+    FileVersionError(u16),
+    InvalidMapData,
+    InvalidBoardCode,
+}
+
+impl fmt::Display for AlertFailure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlertFailure::FileIOError => write!(f, "Failed to access the file."),
+            AlertFailure::FileFormatError => write!(f, "The file is not a valid CharlesMine file."),
+            AlertFailure::FileVersionError(v) => {
+                write!(f, "The file format version {} is not supported.", v)
+            }
+            AlertFailure::InvalidMapData => write!(f, "The file contains an invalid mine field."),
+            AlertFailure::InvalidBoardCode => write!(f, "The board code is not valid."),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum AlertNotice {
+    RecordFinished,
+    PlaybackFinished,
+    FinishedWithHints(usize),
+    FinishedWithHeatmap,
+    FinishedAsPractice,
+    NoGuessFallback,
+    GameSummary(model_stats::GameStats),
+}
+
+impl fmt::Display for AlertNotice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AlertNotice::RecordFinished => write!(f, "Recording finished."),
+            AlertNotice::PlaybackFinished => write!(f, "Playback finished."),
+            AlertNotice::FinishedWithHints(count) => write!(
+                f,
+                "You won with {} hint(s). Games using hints do not count toward best times.",
+                count
+            ),
+            AlertNotice::FinishedWithHeatmap => write!(
+                f,
+                "You won with the heatmap shown. Such games do not count toward best times."
+            ),
+            AlertNotice::FinishedAsPractice => write!(
+                f,
+                "You won a practice game. Games using undo do not count toward best times."
+            ),
+            AlertNotice::NoGuessFallback => write!(
+                f,
+                "No board that can be cleared without guessing was found. This one may need a guess."
+            ),
+            AlertNotice::GameSummary(stats) => write!(
+                f,
+                "{}\nTime: {:.3} s\n3BV: {}\n3BV/s: {:.2}\n\
+                 Clicks: {} (left {}, right {}, chords {})\n\
+                 Wasted clicks: {}\nFlag toggles: {}\nEfficiency: {:.0}%",
+                if stats.won { "You won!" } else { "You lost." },
+                stats.time.as_secs_f64(),
+                stats.bbbv,
+                stats.bbbv_per_second(),
+                stats.clicks.total(),
+                stats.clicks.left,
+                stats.clicks.right,
+                stats.clicks.chords,
+                stats.clicks.wasted,
+                stats.clicks.flag_toggles,
+                stats.efficiency() * 100.0
+            ),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum ViewCommand {
+    Initialize,
+    UpdateZoomRatio(model_config::ZoomRatio),
+    UpdateUIBoardSetting(model_config::BoardSetting),
+    UpdateUIAllowMarks(model_config::AllowMarks),
+    UpdateUINoGuess(model_config::NoGuess),
+    UpdateUISafeStart(model_config::SafeStart),
+    UpdateUIHeatmap(model_config::ShowHeatmap),
+    UpdateUIZoomRatio(model_config::ZoomRatio),
+    UpdateUIGameMode(model_gamemode::GameMode),
+    SetButtonPressed(bool),
+    SetBlockPressed(usize, usize, bool),
+    UnsetBlockPressed(usize, usize, bool),
+    AlertFailure(AlertFailure),
+    AlertNotice(AlertNotice),
+    PromptHeroName(BoardSettingKind),
+    ShowBoardCode(String),
+    Refresh,
+    SetCapture,
+    ReleaseCapture,
+}
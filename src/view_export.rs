@@ -0,0 +1,578 @@
+use crate::model::{BlockDisplayKind, BlockShape, Board, Model};
+use crate::model_config::ZoomRatio;
+use crate::ui_backend::{Point, RGBColor, Size, UiResult};
+use crate::ui_software::SoftwareCanvas;
+use crate::view_assets::{Assets, BlockSpriteSheet};
+use crate::view_command::AlertFailure;
+use crate::view_layout::ViewLayout;
+use std::fs;
+use std::path::Path;
+
+/// Renders the game area as it would appear in the window, without any
+/// button or block held down.
+pub fn render_board(model: &Model, zoom_ratio: ZoomRatio) -> UiResult<SoftwareCanvas> {
+    let mut layout = ViewLayout::new(model.size());
+    layout.update_zoom_ratio(zoom_ratio);
+    SoftwareCanvas::render(&layout, model, &Assets::new())
+}
+
+pub fn export_png(model: &Model, zoom_ratio: ZoomRatio, path: &Path) -> Result<(), AlertFailure> {
+    let canvas = render_board(model, zoom_ratio).map_err(|_| AlertFailure::FileIOError)?;
+    let output = encode_png(canvas.size(), canvas.rgba());
+    fs::write(path, output).map_err(|_| AlertFailure::FileIOError)
+}
+
+/// Whether drawings get row and column numbers, counted from 1, along their
+/// top and left edges.
+#[derive(Copy, Clone, Debug, PartialEq, Default)]
+pub struct CoordinateLabels(pub bool);
+
+pub fn export_svg(
+    board: &Board,
+    labels: CoordinateLabels,
+    path: &Path,
+) -> Result<(), AlertFailure> {
+    fs::write(path, encode_svg(board, labels)).map_err(|_| AlertFailure::FileIOError)
+}
+
+/// Draws the mine field as vector shapes, one triangle per block laid out
+/// like the block sprites, so that it prints at any size.
+pub fn encode_svg(board: &Board, labels: CoordinateLabels) -> String {
+    const MARGIN: usize = 4;
+    const LABEL_SPACE: usize = 16;
+
+    let (rows, columns) = board.size();
+    let area_size = BlockSpriteSheet::calc_block_area_size((rows, columns));
+    let offset = if labels.0 {
+        MARGIN + LABEL_SPACE
+    } else {
+        MARGIN
+    };
+    let origin = Point::new(offset as isize, offset as isize);
+    let (width, height) = (
+        area_size.cx() + offset + MARGIN,
+        area_size.cy() + offset + MARGIN,
+    );
+
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{w}\" height=\"{h}\" \
+         viewBox=\"0 0 {w} {h}\" font-family=\"sans-serif\" text-anchor=\"middle\">\n",
+        w = width,
+        h = height
+    );
+    svg.push_str(&format!(
+        "<rect width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+        width,
+        height,
+        svg_color(RGBColor::SILVER)
+    ));
+
+    if labels.0 {
+        let block_center = |y, x| {
+            let pos = BlockSpriteSheet::calc_block_pos(origin, (y, x));
+            (
+                pos.x() + BlockSpriteSheet::BLOCKSIZE_X as isize / 2,
+                pos.y() + BlockSpriteSheet::BLOCKSIZE_Y as isize / 2,
+            )
+        };
+        for x in 0..columns {
+            let (center_x, _) = block_center(0, x);
+            svg_text(
+                &mut svg,
+                (center_x as f64, offset as f64 - 9.0),
+                9,
+                RGBColor::BLACK,
+                x + 1,
+            );
+        }
+        for y in 0..rows {
+            let (_, center_y) = block_center(y, 0);
+            svg_text(
+                &mut svg,
+                (offset as f64 - 9.0, center_y as f64),
+                9,
+                RGBColor::BLACK,
+                y + 1,
+            );
+        }
+    }
+
+    for y in 0..rows {
+        for x in 0..columns {
+            svg_block(
+                &mut svg,
+                origin,
+                (y, x),
+                board.block_display_kind((y, x), None),
+            );
+        }
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn svg_color(color: RGBColor) -> String {
+    format!("#{:02x}{:02x}{:02x}", color.r(), color.g(), color.b())
+}
+
+fn svg_text<T: std::fmt::Display>(
+    svg: &mut String,
+    (x, y): (f64, f64),
+    font_size: usize,
+    color: RGBColor,
+    text: T,
+) {
+    svg.push_str(&format!(
+        "<text x=\"{}\" y=\"{}\" dy=\"0.35em\" font-size=\"{}\" fill=\"{}\">{}</text>\n",
+        x,
+        y,
+        font_size,
+        svg_color(color),
+        text
+    ));
+}
+
+fn svg_line(svg: &mut String, from: (f64, f64), to: (f64, f64), width: f64, color: RGBColor) {
+    svg.push_str(&format!(
+        "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"{}\" stroke-width=\"{}\"/>\n",
+        from.0,
+        from.1,
+        to.0,
+        to.1,
+        svg_color(color),
+        width
+    ));
+}
+
+fn svg_block(svg: &mut String, origin: Point, (y, x): (usize, usize), kind: BlockDisplayKind) {
+    const CLOSED_COLOR: RGBColor = RGBColor::SILVER;
+    const OPEN_COLOR: RGBColor = RGBColor::new(224, 224, 224);
+    const EXPLODED_COLOR: RGBColor = RGBColor::new(255, 0, 0);
+    const FLAG_COLOR: RGBColor = RGBColor::new(255, 0, 0);
+    const NUMBER_COLORS: [RGBColor; 12] = [
+        RGBColor::new(0, 0, 255),
+        RGBColor::new(0, 128, 0),
+        RGBColor::new(255, 0, 0),
+        RGBColor::new(0, 0, 128),
+        RGBColor::new(128, 0, 0),
+        RGBColor::new(0, 128, 128),
+        RGBColor::BLACK,
+        RGBColor::GRAY,
+        RGBColor::new(128, 0, 128),
+        RGBColor::new(128, 128, 0),
+        RGBColor::new(192, 96, 0),
+        RGBColor::new(64, 64, 64),
+    ];
+
+    let pos = BlockSpriteSheet::calc_block_pos(origin, (y, x));
+    let (w, h) = (
+        BlockSpriteSheet::BLOCKSIZE_X as f64,
+        BlockSpriteSheet::BLOCKSIZE_Y as f64,
+    );
+    let (left, top) = (pos.x() as f64, pos.y() as f64);
+    // marks go to the centroid, which is a third of the way from the base.
+    let (corners, center) = match Board::block_shape(y, x) {
+        BlockShape::DeltaLike => (
+            [(w / 2.0, 0.0), (w, h), (0.0, h)],
+            (left + w / 2.0, top + h * 2.0 / 3.0),
+        ),
+        BlockShape::RevDeltaLike => (
+            [(0.0, 0.0), (w, 0.0), (w / 2.0, h)],
+            (left + w / 2.0, top + h / 3.0),
+        ),
+    };
+    let fill = match kind {
+        BlockDisplayKind::Normal
+        | BlockDisplayKind::MarkedMine
+        | BlockDisplayKind::MarkedQuestionable
+        | BlockDisplayKind::WrongMarkedMine => CLOSED_COLOR,
+        BlockDisplayKind::ExplodedMine => EXPLODED_COLOR,
+        BlockDisplayKind::NotMarkedMine
+        | BlockDisplayKind::PushMarkedQuestionable
+        | BlockDisplayKind::OpenWithNumber(_)
+        | BlockDisplayKind::PushNormal => OPEN_COLOR,
+    };
+    let points = corners
+        .iter()
+        .map(|(dx, dy)| format!("{},{}", left + dx, top + dy))
+        .collect::<Vec<_>>()
+        .join(" ");
+    svg.push_str(&format!(
+        "<polygon points=\"{}\" fill=\"{}\" stroke=\"{}\"/>\n",
+        points,
+        svg_color(fill),
+        svg_color(RGBColor::GRAY)
+    ));
+
+    let (cx, cy) = center;
+    match kind {
+        BlockDisplayKind::OpenWithNumber(0) => {}
+        BlockDisplayKind::OpenWithNumber(n) => {
+            let color = NUMBER_COLORS[(n as usize - 1) % NUMBER_COLORS.len()];
+            svg_text(svg, center, 10, color, n);
+        }
+        BlockDisplayKind::MarkedQuestionable | BlockDisplayKind::PushMarkedQuestionable => {
+            svg_text(svg, center, 10, RGBColor::BLACK, '?');
+        }
+        BlockDisplayKind::MarkedMine | BlockDisplayKind::WrongMarkedMine => {
+            svg.push_str(&format!(
+                "<polygon points=\"{},{} {},{} {},{}\" fill=\"{}\"/>\n",
+                cx + 1.0,
+                cy - 5.0,
+                cx - 5.0,
+                cy - 2.5,
+                cx + 1.0,
+                cy,
+                svg_color(FLAG_COLOR)
+            ));
+            svg_line(
+                svg,
+                (cx + 1.0, cy - 5.0),
+                (cx + 1.0, cy + 4.0),
+                1.0,
+                RGBColor::BLACK,
+            );
+            svg_line(
+                svg,
+                (cx - 3.0, cy + 4.0),
+                (cx + 4.0, cy + 4.0),
+                1.5,
+                RGBColor::BLACK,
+            );
+            if let BlockDisplayKind::WrongMarkedMine = kind {
+                svg_line(
+                    svg,
+                    (cx - 4.0, cy - 5.0),
+                    (cx + 4.0, cy + 5.0),
+                    1.5,
+                    FLAG_COLOR,
+                );
+                svg_line(
+                    svg,
+                    (cx - 4.0, cy + 5.0),
+                    (cx + 4.0, cy - 5.0),
+                    1.5,
+                    FLAG_COLOR,
+                );
+            }
+        }
+        BlockDisplayKind::ExplodedMine | BlockDisplayKind::NotMarkedMine => {
+            svg_line(svg, (cx - 5.0, cy), (cx + 5.0, cy), 1.0, RGBColor::BLACK);
+            svg_line(svg, (cx, cy - 5.0), (cx, cy + 5.0), 1.0, RGBColor::BLACK);
+            svg.push_str(&format!(
+                "<circle cx=\"{}\" cy=\"{}\" r=\"3.5\" fill=\"{}\"/>\n",
+                cx,
+                cy,
+                svg_color(RGBColor::BLACK)
+            ));
+        }
+        BlockDisplayKind::Normal | BlockDisplayKind::PushNormal => {}
+    }
+}
+
+/// Encodes rows of RGBA pixels as a PNG image.
+pub fn encode_png(size: Size, rgba: &[u8]) -> Vec<u8> {
+    const SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    const BIT_DEPTH: u8 = 8;
+    const COLOR_TYPE_RGBA: u8 = 6;
+    const FILTER_NONE: u8 = 0;
+
+    assert_eq!(rgba.len(), size.cx() * size.cy() * 4);
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(size.cx() as u32).to_be_bytes());
+    header.extend_from_slice(&(size.cy() as u32).to_be_bytes());
+    // compression, filter and interlace methods are all the default.
+    header.extend_from_slice(&[BIT_DEPTH, COLOR_TYPE_RGBA, 0, 0, 0]);
+
+    let mut scanlines = Vec::with_capacity(rgba.len() + size.cy());
+    if size.cx() > 0 {
+        for row in rgba.chunks(size.cx() * 4) {
+            scanlines.push(FILTER_NONE);
+            scanlines.extend_from_slice(row);
+        }
+    }
+
+    let mut output = SIGNATURE.to_vec();
+    write_png_chunk(&mut output, b"IHDR", &header);
+    write_png_chunk(&mut output, b"IDAT", &zlib_compress(&scanlines));
+    write_png_chunk(&mut output, b"IEND", &[]);
+    output
+}
+
+fn write_png_chunk(output: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    output.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    let start = output.len();
+    output.extend_from_slice(kind);
+    output.extend_from_slice(data);
+    let crc = crc32(&output[start..]);
+    output.extend_from_slice(&crc.to_be_bytes());
+}
+
+fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for &byte in data {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 {
+                0xEDB8_8320 ^ (crc >> 1)
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+fn adler32(data: &[u8]) -> u32 {
+    const MOD_ADLER: u32 = 65521;
+
+    let (mut a, mut b) = (1u32, 0u32);
+    // sums of this many bytes can't overflow before being reduced.
+    for chunk in data.chunks(5552) {
+        for &byte in chunk {
+            a += byte as u32;
+            b += a;
+        }
+        a %= MOD_ADLER;
+        b %= MOD_ADLER;
+    }
+    (b << 16) | a
+}
+
+/// Wraps a single deflate block, using the fixed Huffman codes, in a zlib
+/// stream. The matcher is simple, but the flat colors of the board compress
+/// well enough with it.
+fn zlib_compress(data: &[u8]) -> Vec<u8> {
+    const WINDOW_SIZE: usize = 32768;
+    const MIN_MATCH: usize = 3;
+    const MAX_MATCH: usize = 258;
+    const MAX_CHAIN: usize = 64;
+    const HASH_BITS: u32 = 15;
+    const NO_POS: usize = usize::MAX;
+
+    fn hash(data: &[u8], pos: usize) -> usize {
+        let v = (data[pos] as u32) << 16 | (data[pos + 1] as u32) << 8 | data[pos + 2] as u32;
+        (v.wrapping_mul(2_654_435_761) >> (32 - HASH_BITS)) as usize
+    }
+
+    let mut writer = BitWriter::default();
+    // deflate with no preset dictionary and a 32K window.
+    writer.bytes.extend_from_slice(&[0x78, 0x01]);
+    // final block, fixed Huffman codes.
+    writer.write_bits(1, 1);
+    writer.write_bits(1, 2);
+
+    let mut head = vec![NO_POS; 1 << HASH_BITS];
+    let mut prev = vec![NO_POS; data.len()];
+    let mut pos = 0;
+    while pos < data.len() {
+        let mut best = (0, 0);
+        if pos + MIN_MATCH <= data.len() {
+            let h = hash(data, pos);
+            let max_len = MAX_MATCH.min(data.len() - pos);
+            let mut candidate = head[h];
+            let mut chain = 0;
+            while candidate != NO_POS && pos - candidate <= WINDOW_SIZE && chain < MAX_CHAIN {
+                let len = (0..max_len)
+                    .take_while(|&i| data[candidate + i] == data[pos + i])
+                    .count();
+                if len > best.0 {
+                    best = (len, pos - candidate);
+                    if len == max_len {
+                        break;
+                    }
+                }
+                candidate = prev[candidate];
+                chain += 1;
+            }
+        }
+
+        let step = if best.0 >= MIN_MATCH {
+            writer.write_length(best.0);
+            writer.write_distance(best.1);
+            best.0
+        } else {
+            writer.write_literal(data[pos] as usize);
+            1
+        };
+        let indexed_end = (pos + step).min(data.len().saturating_sub(MIN_MATCH - 1));
+        for (p, prev_pos) in prev.iter_mut().enumerate().take(indexed_end).skip(pos) {
+            let h = hash(data, p);
+            *prev_pos = head[h];
+            head[h] = p;
+        }
+        pos += step;
+    }
+    writer.write_literal(256);
+    writer.flush();
+
+    let mut output = writer.bytes;
+    output.extend_from_slice(&adler32(data).to_be_bytes());
+    output
+}
+
+#[derive(Default)]
+struct BitWriter {
+    bytes: Vec<u8>,
+    bit_buffer: u32,
+    bit_count: u32,
+}
+
+impl BitWriter {
+    const LENGTH_BASE: [usize; 29] = [
+        3, 4, 5, 6, 7, 8, 9, 10, 11, 13, 15, 17, 19, 23, 27, 31, 35, 43, 51, 59, 67, 83, 99, 115,
+        131, 163, 195, 227, 258,
+    ];
+    const LENGTH_EXTRA: [u32; 29] = [
+        0, 0, 0, 0, 0, 0, 0, 0, 1, 1, 1, 1, 2, 2, 2, 2, 3, 3, 3, 3, 4, 4, 4, 4, 5, 5, 5, 5, 0,
+    ];
+    const DISTANCE_BASE: [usize; 30] = [
+        1, 2, 3, 4, 5, 7, 9, 13, 17, 25, 33, 49, 65, 97, 129, 193, 257, 385, 513, 769, 1025, 1537,
+        2049, 3073, 4097, 6145, 8193, 12289, 16385, 24577,
+    ];
+    const DISTANCE_EXTRA: [u32; 30] = [
+        0, 0, 0, 0, 1, 1, 2, 2, 3, 3, 4, 4, 5, 5, 6, 6, 7, 7, 8, 8, 9, 9, 10, 10, 11, 11, 12, 12,
+        13, 13,
+    ];
+
+    /// Appends `count` bits of `value`, least significant bit first.
+    fn write_bits(&mut self, value: u32, count: u32) {
+        self.bit_buffer |= value << self.bit_count;
+        self.bit_count += count;
+        while self.bit_count >= 8 {
+            self.bytes.push(self.bit_buffer as u8);
+            self.bit_buffer >>= 8;
+            self.bit_count -= 8;
+        }
+    }
+
+    /// Huffman codes are packed starting from their most significant bit.
+    fn write_code(&mut self, code: u32, count: u32) {
+        let reversed = code.reverse_bits() >> (32 - count);
+        self.write_bits(reversed, count);
+    }
+
+    fn write_literal(&mut self, symbol: usize) {
+        let symbol = symbol as u32;
+        match symbol {
+            0..=143 => self.write_code(0x30 + symbol, 8),
+            144..=255 => self.write_code(0x190 + symbol - 144, 9),
+            256..=279 => self.write_code(symbol - 256, 7),
+            _ => self.write_code(0xC0 + symbol - 280, 8),
+        }
+    }
+
+    fn write_length(&mut self, length: usize) {
+        let idx = Self::LENGTH_BASE
+            .iter()
+            .rposition(|&base| base <= length)
+            .unwrap();
+        self.write_literal(257 + idx);
+        self.write_bits(
+            (length - Self::LENGTH_BASE[idx]) as u32,
+            Self::LENGTH_EXTRA[idx],
+        );
+    }
+
+    fn write_distance(&mut self, distance: usize) {
+        let idx = Self::DISTANCE_BASE
+            .iter()
+            .rposition(|&base| base <= distance)
+            .unwrap();
+        self.write_code(idx as u32, 5);
+        self.write_bits(
+            (distance - Self::DISTANCE_BASE[idx]) as u32,
+            Self::DISTANCE_EXTRA[idx],
+        );
+    }
+
+    fn flush(&mut self) {
+        if self.bit_count > 0 {
+            self.bytes.push(self.bit_buffer as u8);
+            self.bit_buffer = 0;
+            self.bit_count = 0;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::ModelCommand;
+    use crate::model_storage::ScratchDir;
+
+    #[test]
+    fn checksums_match_known_values() {
+        assert_eq!(crc32(b"IEND"), 0xAE42_6082);
+        assert_eq!(crc32(b"123456789"), 0xCBF4_3926);
+        assert_eq!(adler32(b"Wikipedia"), 0x11E6_0398);
+    }
+
+    fn opened_board(seed: u64) -> Board {
+        let mut board = Board::new(10, 11, 10);
+        board.set_seed(seed);
+        board.apply_command(&ModelCommand::OpenBlock(4, 5));
+        board
+    }
+
+    #[test]
+    fn svg_draws_a_triangle_per_block() {
+        let mut board = opened_board(7);
+        let mine = board.snapshot_mine_pos_list().unwrap()[0];
+        let (mine_y, mine_x) = (mine / 11, mine % 11);
+        board.apply_command(&ModelCommand::RotateBlockState(mine_y, mine_x));
+
+        let svg = encode_svg(&board, CoordinateLabels(false));
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        let triangles = svg
+            .lines()
+            .filter(|line| line.starts_with("<polygon") && line.contains("stroke="))
+            .count();
+        assert_eq!(triangles, 10 * 11);
+        // the flag.
+        assert_eq!(svg.matches("<polygon").count(), 10 * 11 + 1);
+        assert!(!svg.contains("<circle"));
+        let numbers = (0..10)
+            .flat_map(|y| (0..11).map(move |x| (y, x)))
+            .filter(|&(y, x)| match board.block_display_kind((y, x), None) {
+                BlockDisplayKind::OpenWithNumber(n) => n > 0,
+                _ => false,
+            })
+            .count();
+        assert_eq!(svg.matches("<text").count(), numbers);
+
+        let labeled = encode_svg(&board, CoordinateLabels(true));
+        assert_eq!(labeled.matches("<text").count(), numbers + 10 + 11);
+    }
+
+    #[test]
+    fn svg_shows_mines_after_dying() {
+        let mut board = opened_board(7);
+        let mine = board.snapshot_mine_pos_list().unwrap()[0];
+        let (mine_y, mine_x) = (mine / 11, mine % 11);
+        board.apply_command(&ModelCommand::OpenBlock(mine_y, mine_x));
+        let svg = encode_svg(&board, CoordinateLabels(false));
+        assert_eq!(svg.matches("<circle").count(), 10);
+        let exploded = format!("fill=\"{}\" stroke=", svg_color(RGBColor::new(255, 0, 0)));
+        assert_eq!(svg.matches(&exploded).count(), 1);
+    }
+
+    #[test]
+    fn png_has_the_canvas_size() {
+        // nothing is stored in the directory, so the model has the default config.
+        let dir = ScratchDir::new("export");
+        let model = Model::with_storage(dir.storage());
+        let canvas = render_board(&model, ZoomRatio::Zoom2x).unwrap();
+        let png = encode_png(canvas.size(), canvas.rgba());
+        assert_eq!(&png[..8], b"\x89PNG\r\n\x1a\n");
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(png[16..20], (canvas.size().cx() as u32).to_be_bytes());
+        assert_eq!(png[20..24], (canvas.size().cy() as u32).to_be_bytes());
+        assert_eq!(&png[png.len() - 12..], b"\0\0\0\0IEND\xAE\x42\x60\x82");
+        // the flat colors should compress far below the raw size.
+        assert!(png.len() * 10 < canvas.rgba().len());
+    }
+}
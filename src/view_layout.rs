@@ -0,0 +1,599 @@
+use crate::model::BlockDisplayKind;
+use crate::model::BlockShape;
+use crate::model::Board;
+use crate::model::BoardStatus;
+use crate::model::GameButtonDisplayKind;
+use crate::model::Model;
+use crate::model_config;
+use crate::model_solver::{self, SolverCell, SolverGrid};
+use crate::ui_backend::{Point, RGBColor, Rect, Size, UiBackend, UiCanvas, UiDraw, UiResult};
+use crate::view_assets::BlockSpriteSheet;
+use crate::view_assets::GameButtonSpriteSheet;
+use crate::view_assets::SpriteSheet;
+use crate::view_assets::{Assets, DigitSpriteSheet};
+use std::cell::{Cell, RefCell};
+use std::num::NonZeroUsize;
+
+pub struct LayoutData {
+    block_area_dims: (usize, usize),
+    area_size: Size,
+    block_area_size: Size,
+    digit_pos_1: Point,
+    digit_pos_2: Point,
+    button_pos: Point,
+}
+
+impl LayoutData {
+    pub const DIGITEDGE_LEFT: usize = 15;
+    pub const DIGITEDGE_RIGHT: usize = 15;
+    pub const DIGITEDGE_TOP: usize = 15;
+
+    pub const BUTTONEDGE_TOP: usize = 15;
+
+    pub const MIDDLE_BANNER: usize = 10;
+
+    pub const BLOCK_AREA_EDGE_X: usize = 12;
+    pub const BLOCK_AREA_EDGE_Y: usize = 12;
+    pub const BLOCK_AREA_EDGE_TOP: usize = 12;
+
+    pub const BLOCK_AREA_X: usize = Self::BLOCK_AREA_EDGE_X;
+    pub const BLOCK_AREA_Y: usize = Self::DIGITEDGE_TOP
+        + DigitSpriteSheet::DIGIT_HEIGHT
+        + Self::MIDDLE_BANNER
+        + Self::BLOCK_AREA_EDGE_TOP;
+}
+
+impl LayoutData {
+    fn new(block_area_dims: (usize, usize)) -> Self {
+        let block_area_size = BlockSpriteSheet::calc_block_area_size(block_area_dims);
+        let area_size = Size::new(
+            Self::BLOCK_AREA_X + block_area_size.cx() + Self::BLOCK_AREA_EDGE_X,
+            Self::BLOCK_AREA_Y + block_area_size.cy() + Self::BLOCK_AREA_EDGE_Y,
+        );
+
+        let digit_pos_1 = Point::new(Self::DIGITEDGE_LEFT as _, Self::DIGITEDGE_TOP as _);
+
+        let digit_pos_2 = Point::new(
+            (area_size.cx()
+                - Self::DIGITEDGE_RIGHT
+                - DigitSpriteSheet::DIGIT_WIDTH * DigitPanel::DIGITCOUNT) as _,
+            Self::DIGITEDGE_TOP as _,
+        );
+
+        let button_pos = Point::new(
+            (area_size.cx() - GameButtonSpriteSheet::BUTTON_WIDTH) as isize / 2,
+            Self::BUTTONEDGE_TOP as _,
+        );
+        Self {
+            block_area_dims,
+            area_size,
+            block_area_size,
+            digit_pos_1,
+            digit_pos_2,
+            button_pos,
+        }
+    }
+}
+
+#[derive(PartialEq)]
+pub enum BorderPosition {
+    Inner,
+    Outer,
+}
+
+pub struct ThreeDimBorder {
+    pub rect: Rect,
+    pub border_pos: BorderPosition,
+    pub color_nw: RGBColor,
+    pub color_se: RGBColor,
+}
+
+impl ThreeDimBorder {
+    const BORDER_WIDTH: usize = 2;
+}
+
+struct DigitPanel<'a> {
+    pos: Point,
+    value: isize,
+    assets: &'a Assets,
+}
+
+impl<'a> DigitPanel<'a> {
+    const MAXVALUE: isize = 999;
+    const DIGITCOUNT: usize = 3;
+}
+
+impl UiDraw for ThreeDimBorder {
+    fn draw(self, canvas: &mut dyn UiCanvas) -> UiResult<()> {
+        let deflate = if self.border_pos == BorderPosition::Outer {
+            Some(Self::BORDER_WIDTH)
+        } else {
+            None
+        };
+
+        let rect = if let Some(w) = deflate {
+            self.rect.deflate(w)
+        } else {
+            self.rect
+        };
+
+        canvas.draw_polyline(
+            &[rect.top_right(), rect.top_left(), rect.bottom_left()],
+            Self::BORDER_WIDTH,
+            self.color_nw,
+        )?;
+        canvas.draw_polyline(
+            &[rect.bottom_left(), rect.bottom_right(), rect.top_right()],
+            Self::BORDER_WIDTH,
+            self.color_se,
+        )?;
+
+        Ok(())
+    }
+}
+
+impl<'a> UiDraw for GameButton<'a> {
+    fn draw(self, canvas: &mut dyn UiCanvas) -> UiResult<()> {
+        let sprite_idx = GameButtonSpriteSheet::sprite_index(self.state);
+        self.assets
+            .gamebutton_sheet
+            .draw_sprite(canvas, self.pos, sprite_idx)?;
+
+        Ok(())
+    }
+}
+
+impl<'a> UiDraw for MineBlock<'a> {
+    fn draw(self, canvas: &mut dyn UiCanvas) -> UiResult<()> {
+        let sprite_idx =
+            BlockSpriteSheet::sprite_index(self.block_shape_dir, self.block_display_kind);
+        let block_draw_pos = BlockSpriteSheet::calc_block_pos(self.minefield_pos, self.block_pos);
+        self.assets
+            .block_sheet
+            .draw_sprite(canvas, block_draw_pos, sprite_idx)?;
+
+        Ok(())
+    }
+}
+
+impl<'a> UiDraw for DigitPanel<'a> {
+    fn draw(self, canvas: &mut dyn UiCanvas) -> UiResult<()> {
+        let value_abs = self.value.abs() as usize;
+        let neg = self.value.is_negative();
+
+        for i in 0..Self::DIGITCOUNT {
+            let sprite_idx = if i == 0 && neg {
+                DigitSpriteSheet::sprite_index_neg()
+            } else {
+                let digit = (value_abs % 10_usize.pow(Self::DIGITCOUNT as u32 - i as u32))
+                    / 10_usize.pow(Self::DIGITCOUNT as u32 - i as u32 - 1);
+                DigitSpriteSheet::sprite_index_digit(digit)
+            };
+
+            self.assets.digits_sheet.draw_sprite(
+                canvas,
+                self.pos
+                    .offset((i * DigitSpriteSheet::DIGIT_WIDTH) as isize, 0),
+                sprite_idx,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Paints every other scanline of a block, from green for safe to red for
+/// certain mines, so that the sprite underneath remains visible.
+struct HeatmapBlock {
+    minefield_pos: Point,
+    block_pos: (usize, usize),
+    block_shape_dir: BlockShape,
+    probability: f64,
+}
+
+impl UiDraw for HeatmapBlock {
+    fn draw(self, canvas: &mut dyn UiCanvas) -> UiResult<()> {
+        let p = self.probability.clamp(0.0, 1.0);
+        let color = RGBColor::new((255.0 * p) as u8, (192.0 * (1.0 - p)) as u8, 0);
+        let origin = BlockSpriteSheet::calc_block_pos(self.minefield_pos, self.block_pos);
+        let width = BlockSpriteSheet::BLOCKSIZE_X as isize;
+        let height = BlockSpriteSheet::BLOCKSIZE_Y as isize;
+
+        for row in (3..height - 2).step_by(2) {
+            let depth = match self.block_shape_dir {
+                BlockShape::DeltaLike => row,
+                BlockShape::RevDeltaLike => height - 1 - row,
+            };
+            let half_width = width / 2 * depth / height - 2;
+            if half_width <= 0 {
+                continue;
+            }
+            canvas.draw_polyline(
+                &[
+                    origin.offset(width / 2 - half_width, row),
+                    origin.offset(width / 2 + half_width, row),
+                ],
+                1,
+                color,
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+struct HintBlock {
+    minefield_pos: Point,
+    block_pos: (usize, usize),
+    block_shape_dir: BlockShape,
+}
+
+impl UiDraw for HintBlock {
+    fn draw(self, canvas: &mut dyn UiCanvas) -> UiResult<()> {
+        let origin = BlockSpriteSheet::calc_block_pos(self.minefield_pos, self.block_pos);
+        let right = BlockSpriteSheet::BLOCKSIZE_X as isize - 3;
+        let bottom = BlockSpriteSheet::BLOCKSIZE_Y as isize - 3;
+        let middle = BlockSpriteSheet::BLOCKSIZE_X as isize / 2;
+        let corners = match self.block_shape_dir {
+            BlockShape::DeltaLike => [(middle, 3), (right, bottom), (3, bottom)],
+            BlockShape::RevDeltaLike => [(3, 3), (right, 3), (middle, bottom)],
+        };
+
+        canvas.draw_polyline(
+            &[
+                origin.offset(corners[0].0, corners[0].1),
+                origin.offset(corners[1].0, corners[1].1),
+                origin.offset(corners[2].0, corners[2].1),
+                origin.offset(corners[0].0, corners[0].1),
+            ],
+            2,
+            RGBColor::new(0, 128, 255),
+        )?;
+
+        Ok(())
+    }
+}
+
+struct GameButton<'a> {
+    pos: Point,
+    state: GameButtonDisplayKind,
+    assets: &'a Assets,
+}
+
+struct MineBlock<'a> {
+    minefield_pos: Point,
+    block_pos: (usize, usize),
+    block_shape_dir: BlockShape,
+    block_display_kind: BlockDisplayKind,
+    assets: &'a Assets,
+}
+
+trait BoardDisplayValue {
+    fn display_value_mine_left(&self) -> isize;
+    fn display_value_time(&self) -> isize;
+}
+
+impl BoardDisplayValue for Board {
+    fn display_value_mine_left(&self) -> isize {
+        self.goal_mark_count() as isize - self.cur_mark_count() as isize
+    }
+
+    fn display_value_time(&self) -> isize {
+        use std::cmp::min;
+        let v = self.elapsed_time().as_secs();
+        min(v, DigitPanel::MAXVALUE as _) as isize
+    }
+}
+
+#[derive(PartialEq, Debug, Clone)]
+pub enum GameTarget {
+    GameButton,
+    FieldBlock { y: usize, x: usize },
+    Other,
+}
+
+struct LayoutState {
+    button_pressed: Cell<bool>,
+    window_captured: Cell<bool>,
+    block_pressed: Cell<Option<(usize, usize, bool)>>,
+}
+
+impl LayoutState {
+    fn new() -> Self {
+        LayoutState {
+            button_pressed: Cell::new(false),
+            window_captured: Cell::new(false),
+            block_pressed: Cell::new(None),
+        }
+    }
+}
+
+struct LayoutZoom {
+    ratio: NonZeroUsize,
+}
+
+impl LayoutZoom {
+    fn new() -> Self {
+        Self::new_with_ratio(1)
+    }
+
+    fn new_with_ratio(v: usize) -> Self {
+        LayoutZoom {
+            ratio: NonZeroUsize::new(v).unwrap(),
+        }
+    }
+    fn update_canvas(&self, canvas: &mut dyn UiCanvas) -> UiResult<()> {
+        let v = self.ratio.get();
+        if v == 1 {
+            return Ok(());
+        }
+        canvas.set_zoom(v)
+    }
+
+    fn zoom_size(&self, size: Size) -> Size {
+        let v = self.ratio.get();
+        if v == 1 {
+            return size;
+        }
+        Size::new(size.cx() * v, size.cy() * v)
+    }
+
+    fn unzoom_point(&self, point: Point) -> Point {
+        let v = self.ratio.get();
+        if v == 1 {
+            return point;
+        }
+        Point::new(point.x() / v as isize, point.y() / v as isize)
+    }
+}
+
+impl From<model_config::ZoomRatio> for LayoutZoom {
+    fn from(r: model_config::ZoomRatio) -> Self {
+        match r {
+            model_config::ZoomRatio::Zoom1x => Self::new_with_ratio(1),
+            model_config::ZoomRatio::Zoom2x => Self::new_with_ratio(2),
+            model_config::ZoomRatio::Zoom3x => Self::new_with_ratio(3),
+        }
+    }
+}
+
+struct HeatmapCache {
+    cells: Vec<SolverCell>,
+    probabilities: Option<Vec<f64>>,
+}
+
+/// Layout and drawing of the game area, shared by every UI backend.
+pub struct ViewLayout {
+    layout_data: LayoutData,
+    layout_state: LayoutState,
+    layout_zoom: LayoutZoom,
+    heatmap_cache: RefCell<Option<HeatmapCache>>,
+}
+
+impl ViewLayout {
+    pub fn new(block_area_dims: (usize, usize)) -> Self {
+        ViewLayout {
+            layout_data: LayoutData::new(block_area_dims),
+            layout_state: LayoutState::new(),
+            layout_zoom: LayoutZoom::new(),
+            heatmap_cache: RefCell::new(None),
+        }
+    }
+
+    pub fn update_zoom_ratio(&mut self, ratio: model_config::ZoomRatio) {
+        self.layout_zoom = LayoutZoom::from(ratio);
+    }
+
+    pub fn regenerate_layout_data(&mut self, (y, x): (usize, usize)) {
+        self.layout_data = LayoutData::new((y, x));
+    }
+
+    /// Size of the whole game area after zooming.
+    pub fn client_size(&self) -> Size {
+        self.layout_zoom.zoom_size(self.layout_data.area_size)
+    }
+
+    pub fn draw(&self, canvas: &mut dyn UiCanvas, model: &Model, assets: &Assets) -> UiResult<()> {
+        self.layout_zoom.update_canvas(canvas)?;
+        canvas
+            .draw(ThreeDimBorder {
+                rect: Rect::new(Point::ORIGIN, self.layout_data.area_size),
+                border_pos: BorderPosition::Inner,
+                color_nw: RGBColor::WHITE,
+                color_se: RGBColor::GRAY,
+            })?
+            .draw(ThreeDimBorder {
+                rect: Rect::new(
+                    Point::new(
+                        LayoutData::BLOCK_AREA_X as _,
+                        LayoutData::BUTTONEDGE_TOP as _,
+                    ),
+                    Size::new(
+                        self.layout_data.block_area_size.cx(),
+                        GameButtonSpriteSheet::BUTTON_HEIGHT,
+                    ),
+                ),
+                border_pos: BorderPosition::Outer,
+                color_nw: RGBColor::GRAY,
+                color_se: RGBColor::WHITE,
+            })?
+            .draw(ThreeDimBorder {
+                rect: Rect::new(
+                    Point::new(LayoutData::BLOCK_AREA_X as _, LayoutData::BLOCK_AREA_Y as _),
+                    self.layout_data.block_area_size,
+                ),
+                border_pos: BorderPosition::Outer,
+                color_nw: RGBColor::GRAY,
+                color_se: RGBColor::WHITE,
+            })?
+            .draw(DigitPanel {
+                pos: self.layout_data.digit_pos_1,
+                value: model.display_value_mine_left(),
+                assets: &assets,
+            })?
+            .draw(DigitPanel {
+                pos: self.layout_data.digit_pos_2,
+                value: model.display_value_time(),
+                assets: &assets,
+            })?
+            .draw(GameButton {
+                pos: self.layout_data.button_pos,
+                state: model.game_button_display_kind(
+                    self.layout_state.button_pressed.get(),
+                    self.layout_state.window_captured.get(),
+                ),
+                assets: &assets,
+            })?
+            .draw_from_iter((0..model.size().0).flat_map(move |y| {
+                (0..model.size().1).map(move |x| MineBlock {
+                    minefield_pos: Point::new(
+                        LayoutData::BLOCK_AREA_X as isize,
+                        LayoutData::BLOCK_AREA_Y as isize,
+                    ),
+                    block_pos: (y, x),
+                    block_shape_dir: Board::block_shape(y, x),
+                    block_display_kind: model
+                        .block_display_kind((y, x), self.layout_state.block_pressed.get()),
+                    assets: &assets,
+                })
+            }))?;
+        if model.is_paused() {
+            return Ok(());
+        }
+        if model.config().show_heatmap.0 {
+            if let BoardStatus::Going(..) = model.status() {
+                self.draw_heatmap(canvas, model)?;
+            }
+        }
+        if let Some((y, x)) = model.hint_pos() {
+            canvas.draw(HintBlock {
+                minefield_pos: Point::new(
+                    LayoutData::BLOCK_AREA_X as isize,
+                    LayoutData::BLOCK_AREA_Y as isize,
+                ),
+                block_pos: (y, x),
+                block_shape_dir: Board::block_shape(y, x),
+            })?;
+        }
+        Ok(())
+    }
+
+    fn draw_heatmap(&self, canvas: &mut dyn UiCanvas, model: &Model) -> UiResult<()> {
+        let grid = SolverGrid::from_board(model);
+        let mut cache = self.heatmap_cache.borrow_mut();
+        if cache
+            .as_ref()
+            .map_or(true, |cache| cache.cells != grid.cells())
+        {
+            *cache = Some(HeatmapCache {
+                cells: grid.cells().to_vec(),
+                probabilities: model_solver::mine_probabilities(&grid),
+            });
+        }
+        let probabilities = match cache
+            .as_ref()
+            .and_then(|cache| cache.probabilities.as_ref())
+        {
+            Some(probabilities) => probabilities,
+            None => return Ok(()),
+        };
+        canvas.draw_from_iter(
+            (0..probabilities.len())
+                .filter(|&idx| grid.cell(idx) == SolverCell::Unknown)
+                .map(|idx| {
+                    let (y, x) = grid.position(idx);
+                    HeatmapBlock {
+                        minefield_pos: Point::new(
+                            LayoutData::BLOCK_AREA_X as isize,
+                            LayoutData::BLOCK_AREA_Y as isize,
+                        ),
+                        block_pos: (y, x),
+                        block_shape_dir: Board::block_shape(y, x),
+                        probability: probabilities[idx],
+                    }
+                }),
+        )?;
+        Ok(())
+    }
+
+    pub fn hit_test<B: UiBackend>(&self, point: Point) -> GameTarget {
+        let point = self.layout_zoom.unzoom_point(point);
+        let button_size = Size::new(
+            GameButtonSpriteSheet::BUTTON_WIDTH,
+            GameButtonSpriteSheet::BUTTON_HEIGHT,
+        );
+        let button_rect = Rect::new(self.layout_data.button_pos, button_size);
+        if button_rect.contains(point) {
+            return GameTarget::GameButton;
+        }
+
+        // this is div_euc that is not stabilized yet.
+        fn floor_div(lhs: isize, rhs: isize) -> isize {
+            let q = lhs / rhs;
+            if lhs % rhs < 0 {
+                return if rhs > 0 { q - 1 } else { q + 1 };
+            }
+            q
+        }
+
+        let y_idx = floor_div(
+            point.y() - LayoutData::BLOCK_AREA_Y as isize,
+            BlockSpriteSheet::BLOCKSIZE_Y as isize,
+        ) as isize;
+        if 0 <= y_idx && y_idx < self.layout_data.block_area_dims.0 as isize {
+            let x_idx_min = floor_div(
+                point.x()
+                    - LayoutData::BLOCK_AREA_X as isize
+                    - (BlockSpriteSheet::BLOCKSIZE_X as isize - 1),
+                BlockSpriteSheet::BLOCKDELTA_X as _,
+            ) as isize;
+            let x_idx_max = floor_div(
+                point.x() - LayoutData::BLOCK_AREA_X as isize,
+                BlockSpriteSheet::BLOCKDELTA_X as _,
+            ) as isize;
+            for x_idx in x_idx_min..=x_idx_max {
+                if 0 <= x_idx && x_idx < self.layout_data.block_area_dims.1 as isize {
+                    let x_offset = point.x() as usize
+                        - LayoutData::BLOCK_AREA_X
+                        - BlockSpriteSheet::BLOCKDELTA_X * x_idx as usize;
+                    let y_offset = point.y() as usize
+                        - LayoutData::BLOCK_AREA_Y
+                        - BlockSpriteSheet::BLOCKDELTA_Y * y_idx as usize;
+                    if x_offset >= BlockSpriteSheet::BLOCKSIZE_X {
+                        continue;
+                    }
+                    if BlockSpriteSheet::hit_test_shape::<B>(
+                        Board::block_shape(y_idx as usize, x_idx as usize),
+                        (y_offset, x_offset),
+                    )
+                    .expect("Failed to hit test.")
+                    {
+                        return GameTarget::FieldBlock {
+                            y: y_idx as usize,
+                            x: x_idx as usize,
+                        };
+                    }
+                }
+            }
+        }
+        GameTarget::Other
+    }
+
+    pub fn set_button_pressed(&self, pressed: bool) {
+        self.layout_state.button_pressed.set(pressed);
+    }
+
+    pub fn set_window_captured(&self, window_captured: bool) {
+        self.layout_state.window_captured.set(window_captured);
+    }
+
+    pub fn set_block_pressed(&self, y: usize, x: usize, blast: bool) {
+        self.layout_state.block_pressed.set(Some((y, x, blast)));
+    }
+
+    pub fn unset_block_pressed(&self, y: usize, x: usize, blast: bool) {
+        if self.layout_state.block_pressed.get() == Some((y, x, blast)) {
+            self.layout_state.block_pressed.set(None)
+        }
+    }
+}